@@ -1,10 +1,14 @@
 use bevy::prelude::*;
 use era_automation::{
+    blueprint::BlueprintPlugin,
     consts::CHUNK_LOAD_RADIUS,
     container::ContainerPlugin,
     crafting::CraftingPlugin,
     debug::DebugPlugin,
+    energy_stats::EnergyStatsPlugin,
     ground_items::GroundItemPlugin,
+    inventory::InventoryPlugin,
+    items::ItemPlugin,
     knowledge::KnowledgePlugin,
     machines::MachinePlugin,
     map::{ChunkLUT, CreateChunk, MapPlugin, WorldPos},
@@ -12,6 +16,7 @@ use era_automation::{
     player::{Player, PlayerPlugin},
     resources::ResourcePlugin,
     sprites::SpritePlugin,
+    tools::ToolPlugin,
     village::VillagePlugin,
     weather::WeatherPlugin,
 };
@@ -28,9 +33,14 @@ fn main() {
         .add_plugins(NotificationPlugin)
         .add_plugins(CraftingPlugin)
         .add_plugins(GroundItemPlugin)
+        .add_plugins(InventoryPlugin)
+        .add_plugins(ItemPlugin)
         .add_plugins(ContainerPlugin)
+        .add_plugins(ToolPlugin)
         .add_plugins(DebugPlugin)
+        .add_plugins(EnergyStatsPlugin)
         .add_plugins(MachinePlugin)
+        .add_plugins(BlueprintPlugin)
         .add_plugins(WeatherPlugin)
         .add_systems(Update, spawn_chunks)
         .run();
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{
+    items::ItemType,
+    machines::{Face, Ports, PowerPriority},
+    map::TilePos,
+};
+
+/// A single machine in a blueprint
+#[derive(Clone, Debug)]
+pub struct BlueprintEntry {
+    /// Position relative to the blueprint's origin
+    pub offset: IVec2,
+    /// Item that the machine is built from
+    pub item_type: ItemType,
+    pub direction: IVec2,
+    pub priority: PowerPriority,
+    pub ports: Option<Ports>,
+}
+
+/// Write a list of faces as comma separated names, or `-` if there aren't any
+fn faces_to_text(faces: &[Face]) -> String {
+    if faces.is_empty() {
+        "-".to_string()
+    } else {
+        faces
+            .iter()
+            .map(|face| format!("{face:?}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Read a list of faces written by `faces_to_text`
+fn faces_from_text(text: &str) -> Result<Vec<Face>, ParseBlueprintError> {
+    if text == "-" {
+        return Ok(vec![]);
+    }
+
+    text.split(',')
+        .map(|face| face.parse().map_err(ParseBlueprintError::UnknownFace))
+        .try_collect()
+}
+
+/// A layout of machines that can be copied & stamped elsewhere. Stored facing +X.
+///
+/// Saved to disk as text, with one machine per line:
+/// `<offset x> <offset y> <item type> <direction x> <direction y> <priority> [<inputs> <outputs>]`
+///
+/// Inputs & outputs are comma separated `Face`s, and are left off for machines without ports.
+///
+/// Tiers aren't kept, as ghosts are built at the tier of whichever machine the player uses.
+/// Picker-upper filters aren't kept either, so built picker-uppers keep whatever filter the held
+/// one had.
+#[derive(Clone, Debug, Default)]
+pub struct Blueprint(pub Vec<BlueprintEntry>);
+
+impl fmt::Display for Blueprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.0 {
+            write!(
+                f,
+                "{} {} {:?} {} {} {:?}",
+                entry.offset.x,
+                entry.offset.y,
+                entry.item_type,
+                entry.direction.x,
+                entry.direction.y,
+                entry.priority
+            )?;
+            if let Some(ports) = &entry.ports {
+                write!(
+                    f,
+                    " {} {}",
+                    faces_to_text(&ports.inputs),
+                    faces_to_text(&ports.outputs)
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Blueprint {
+    type Err = ParseBlueprintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ParseBlueprintError::*;
+
+        let entries = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                let Some(([x, y, item_type, dx, dy, priority], ports)) = fields.split_at_checked(6)
+                else {
+                    return Err(WrongFieldCount(i + 1));
+                };
+                let ports = match ports {
+                    [] => None,
+                    [inputs, outputs] => Some(Ports::new(
+                        faces_from_text(inputs)?,
+                        faces_from_text(outputs)?,
+                    )),
+                    _ => return Err(WrongFieldCount(i + 1)),
+                };
+
+                let number = |field: &str| field.parse::<i32>().map_err(|_| InvalidNumber(i + 1));
+
+                Ok(BlueprintEntry {
+                    offset: IVec2::new(number(x)?, number(y)?),
+                    item_type: item_type.parse().map_err(UnknownItem)?,
+                    direction: IVec2::new(number(dx)?, number(dy)?),
+                    priority: priority.parse().map_err(UnknownPriority)?,
+                    ports,
+                })
+            })
+            .try_collect()?;
+
+        Ok(Self(entries))
+    }
+}
+
+/// Error types when a blueprint file can't be read
+#[derive(Debug)]
+pub enum ParseBlueprintError {
+    /// Line number which doesn't have the right number of fields
+    WrongFieldCount(usize),
+    /// Line number with a position or direction that isn't an integer
+    InvalidNumber(usize),
+    UnknownItem(String),
+    UnknownPriority(String),
+    UnknownFace(String),
+}
+
+/// Saved blueprints, and which one will be stamped next
+#[derive(Resource, Default)]
+pub struct Blueprints {
+    pub saved: Vec<Blueprint>,
+    pub selected: usize,
+}
+
+/// Corner where the player started dragging out a selection box
+#[derive(Resource, Default)]
+pub struct BlueprintSelection(pub Option<TilePos>);
+
+/// Machine waiting to be built as part of a stamped blueprint
+#[derive(Component)]
+pub struct BlueprintGhost {
+    pub item_type: ItemType,
+    pub direction: IVec2,
+    pub priority: PowerPriority,
+    pub ports: Option<Ports>,
+}
@@ -0,0 +1,31 @@
+mod components;
+mod systems;
+
+use bevy::prelude::*;
+pub use components::*;
+use systems::*;
+
+use crate::utils::run_if::{key_just_pressed, mouse_just_pressed, mouse_just_released};
+
+pub struct BlueprintPlugin;
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Blueprints>()
+            .init_resource::<BlueprintSelection>()
+            .add_systems(Startup, load_blueprints)
+            .add_systems(
+                Update,
+                (
+                    // Drag out a box with the right mouse button to copy machines
+                    start_selection.run_if(mouse_just_pressed(MouseButton::Right)),
+                    draw_selection,
+                    finish_selection.run_if(mouse_just_released(MouseButton::Right)),
+                    cycle_blueprint.run_if(key_just_pressed(KeyCode::BracketRight)),
+                    stamp_blueprint.run_if(key_just_pressed(KeyCode::KeyV)),
+                    clear_blueprint_ghosts.run_if(key_just_pressed(KeyCode::KeyX)),
+                    build_blueprint_ghosts.run_if(key_just_pressed(KeyCode::KeyB)),
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,283 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+
+use super::components::*;
+use crate::{
+    consts::{BLUEPRINT_DIR, TILE_RAW_SIZE, Z_GHOST},
+    items::ItemType,
+    machines::{
+        Direction, Footprint, Machine, MachineLUT, MachinePlacement, Placed, PlacementDirection,
+        Ports, PowerPriority,
+        systems::{can_place, place_held_machine, placement_tile},
+    },
+    map::{Chunks, TerrainData, TilePos, WorldPos},
+    player::{CursorPos, HeldBy, Holding, Player},
+    resources::ResourceNodeLUT,
+    sprites::{GetSprite, SpriteSheets},
+};
+
+/// Load any blueprints saved to disk
+pub fn load_blueprints(mut blueprints: ResMut<Blueprints>) {
+    let Ok(dir) = fs::read_dir(BLUEPRINT_DIR) else {
+        // Nothing saved yet
+        return;
+    };
+
+    let mut paths = dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let blueprint = fs::read_to_string(&path)
+            .map_err(|err| format!("{err}"))
+            .and_then(|text| text.parse::<Blueprint>().map_err(|err| format!("{err:?}")));
+
+        match blueprint {
+            Ok(blueprint) => {
+                info!("Loaded blueprint {:?}", path);
+                blueprints.saved.push(blueprint);
+            }
+            Err(err) => warn!("Couldn't load blueprint {:?}: {}", path, err),
+        }
+    }
+}
+
+/// Save a blueprint to the next free file on disk
+fn save_blueprint(blueprint: &Blueprint) -> std::io::Result<()> {
+    let dir = Path::new(BLUEPRINT_DIR);
+    fs::create_dir_all(dir)?;
+
+    let path = (0..)
+        .map(|i| dir.join(format!("blueprint_{i}.txt")))
+        .find(|path| !path.exists())
+        .expect("Ran out of blueprint file names");
+
+    info!("Saving blueprint to {:?}", path);
+    fs::write(path, blueprint.to_string())
+}
+
+/// Start dragging out a selection box at the cursor
+pub fn start_selection(cursor: Res<CursorPos>, mut selection: ResMut<BlueprintSelection>) {
+    selection.0 = cursor.tile();
+}
+
+/// Outline the selection box while it's being dragged out
+pub fn draw_selection(
+    selection: Res<BlueprintSelection>,
+    cursor: Res<CursorPos>,
+    mut gizmos: Gizmos,
+) {
+    let (Some(start), Some(end)) = (selection.0, cursor.tile()) else {
+        return;
+    };
+
+    let min = start.0.min(end.0).as_vec2() - 0.5;
+    let max = start.0.max(end.0).as_vec2() + 0.5;
+    gizmos.rect_2d((min + max) / 2., max - min, Color::srgb(0.3, 0.6, 1.));
+}
+
+/// Copy the placed machines inside the selection box into a new blueprint
+pub fn finish_selection(
+    mut selection: ResMut<BlueprintSelection>,
+    cursor: Res<CursorPos>,
+    machines: Query<
+        (
+            &TilePos,
+            &ItemType,
+            &Direction,
+            Option<&PowerPriority>,
+            Option<&Ports>,
+        ),
+        (With<Placed>, With<Machine>),
+    >,
+    mut blueprints: ResMut<Blueprints>,
+) {
+    let (Some(start), Some(end)) = (selection.0.take(), cursor.tile()) else {
+        return;
+    };
+
+    let min = start.0.min(end.0);
+    let max = start.0.max(end.0);
+
+    let mut entries = machines
+        .iter()
+        .filter(|(tile_pos, ..)| tile_pos.0.cmpge(min).all() && tile_pos.0.cmple(max).all())
+        .map(
+            |(tile_pos, item_type, direction, priority, ports)| BlueprintEntry {
+                offset: tile_pos.0 - min,
+                item_type: *item_type,
+                direction: direction.0,
+                priority: priority.copied().unwrap_or_default(),
+                ports: ports.cloned(),
+            },
+        )
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        // Nothing to copy
+        return;
+    }
+    entries.sort_by_key(|entry| (entry.offset.y, entry.offset.x));
+
+    let blueprint = Blueprint(entries);
+    info!("Copied blueprint with {} machines", blueprint.0.len());
+
+    if let Err(err) = save_blueprint(&blueprint) {
+        warn!("Couldn't save blueprint: {}", err);
+    }
+
+    blueprints.saved.push(blueprint);
+    blueprints.selected = blueprints.saved.len() - 1;
+}
+
+/// Select the next saved blueprint
+pub fn cycle_blueprint(mut blueprints: ResMut<Blueprints>) {
+    if blueprints.saved.is_empty() {
+        return;
+    }
+
+    blueprints.selected = (blueprints.selected + 1) % blueprints.saved.len();
+    info!("Selected blueprint {}", blueprints.selected);
+}
+
+/// Stamp the selected blueprint at the placement tile, rotated to the placement direction. Each
+/// machine is queued up as a ghost to be built.
+pub fn stamp_blueprint(
+    blueprints: Res<Blueprints>,
+    player: Single<&WorldPos, With<Player>>,
+    cursor: Res<CursorPos>,
+    direction: Res<PlacementDirection>,
+    ghosts: Query<(Entity, &TilePos), With<BlueprintGhost>>,
+    machines: Res<MachineLUT>,
+    resources: Res<ResourceNodeLUT>,
+    terrain: Chunks<&TerrainData>,
+    sprite_sheets: Res<SpriteSheets>,
+    mut commands: Commands,
+) {
+    let Some(blueprint) = blueprints.saved.get(blueprints.selected) else {
+        // No blueprints yet
+        return;
+    };
+
+    let origin = placement_tile(&player, &cursor);
+    info!(
+        "Stamping blueprint {} at {:?}",
+        blueprints.selected, origin.0
+    );
+
+    for entry in &blueprint.0 {
+        let tile_pos = origin + direction.0.rotate(entry.offset);
+        let machine_direction = direction.0.rotate(entry.direction);
+
+        // Ghosts can be queued out of reach, so check from the tile itself
+        if !can_place(
+            &tile_pos,
+            &entry.item_type.footprint(),
+            machine_direction,
+            &tile_pos.as_world_pos(),
+            &machines,
+            &resources,
+            &terrain,
+        ) {
+            // Blocked
+            continue;
+        }
+
+        // Replace any ghost that's already here
+        for (ghost, _) in ghosts.iter().filter(|(_, pos)| **pos == tile_pos) {
+            commands.entity(ghost).despawn();
+        }
+
+        let mut sprite = entry.item_type.get_sprite(&sprite_sheets);
+        sprite.color = Color::srgba(0.5, 0.7, 1., 0.5);
+        commands.spawn((
+            BlueprintGhost {
+                item_type: entry.item_type,
+                direction: machine_direction,
+                priority: entry.priority,
+                ports: entry.ports.clone(),
+            },
+            tile_pos,
+            sprite,
+            tile_pos
+                .as_transform(Z_GHOST)
+                .with_rotation(Quat::from_rotation_z(
+                    machine_direction.as_vec2().to_angle(),
+                ))
+                .with_scale(1. / TILE_RAW_SIZE.as_vec2().extend(1.)),
+        ));
+    }
+}
+
+/// Remove all queued blueprint ghosts
+pub fn clear_blueprint_ghosts(ghosts: Query<Entity, With<BlueprintGhost>>, mut commands: Commands) {
+    for ghost in ghosts {
+        commands.entity(ghost).despawn();
+    }
+}
+
+/// Build the closest ghost within reach using the matching machine held by the player
+pub fn build_blueprint_ghosts(
+    player: Single<(&WorldPos, &Holding), With<Player>>,
+    held_machines: Query<(Entity, &Machine, &ItemType, &Footprint), With<HeldBy>>,
+    ghosts: Query<(Entity, &TilePos, &BlueprintGhost)>,
+    mut machines: ResMut<MachineLUT>,
+    resources: Res<ResourceNodeLUT>,
+    terrain: Chunks<&TerrainData>,
+    mut commands: Commands,
+    mut placements: MessageWriter<MachinePlacement>,
+) {
+    let (player_pos, holding) = *player;
+
+    let Some((machine, machine_type, item_type, footprint)) = holding
+        .iter()
+        .find_map(|entity| held_machines.get(entity).ok())
+    else {
+        // Not holding a machine
+        return;
+    };
+
+    // Closest matching ghost which can be built right now
+    let Some((ghost, tile_pos, blueprint_ghost)) = ghosts
+        .iter()
+        .filter(|(_, tile_pos, ghost)| {
+            ghost.item_type == *item_type
+                && can_place(
+                    tile_pos,
+                    footprint,
+                    ghost.direction,
+                    player_pos,
+                    &machines,
+                    &resources,
+                    &terrain,
+                )
+        })
+        .min_by(|(_, p1, _), (_, p2, _)| {
+            let d1 = p1.as_world_pos().0.distance_squared(player_pos.0);
+            let d2 = p2.as_world_pos().0.distance_squared(player_pos.0);
+            d1.total_cmp(&d2)
+        })
+    else {
+        info!("No ghosts to build with this machine within reach");
+        return;
+    };
+
+    place_held_machine(
+        &mut commands,
+        &mut machines,
+        &mut placements,
+        machine,
+        machine_type,
+        footprint,
+        *tile_pos,
+        blueprint_ghost.direction,
+    );
+    commands.entity(machine).insert(blueprint_ghost.priority);
+    if let Some(ports) = &blueprint_ghost.ports {
+        commands.entity(machine).insert(ports.clone());
+    }
+    commands.entity(ghost).despawn();
+}
@@ -21,10 +21,23 @@ pub const CHUNK_LOAD_RADIUS: i32 = 3;
 /// Player's interact radius in world units
 pub const PLAYER_REACH: f32 = 2.;
 
+/// Number of slots in the player's inventory, which are all shown on the hotbar
+pub const INVENTORY_SIZE: usize = 9;
+
+/// Wear a machine picks up for each action it completes. Machines break at 1.
+pub const MACHINE_WEAR_PER_ACTION: f32 = 1. / 200.;
+/// Wear past which machines start slowing down
+pub const MACHINE_WEAR_SLOWDOWN: f32 = 0.5;
+/// Wood needed to fully repair a broken machine
+pub const MACHINE_REPAIR_COST: usize = 10;
+/// Items put on a belt only stack onto items that haven't moved further along it than this
+pub const BELT_STACK_PROGRESS: f32 = 0.25;
+
 /// Z layers for sprite rendering
 pub const Z_TERRAIN: f32 = 0.;
 pub const Z_RESOURCES: f32 = 1.;
 pub const Z_GROUND_ITEM: f32 = 2.;
+pub const Z_GHOST: f32 = 2.5;
 pub const Z_PLAYER: f32 = 3.;
 /// Z relative to Z_PLAYER
 pub const Z_HELD_ITEM: f32 = 1e-1;
@@ -32,6 +45,8 @@ pub const Z_HELD_ITEM: f32 = 1e-1;
 pub const Z_CONTAINED_ITEM: f32 = -1e-2;
 /// Z relative to Z_RESOURCES
 pub const Z_TRANSPORTED_ITEM: f32 = 1e-1;
+/// Z relative to the item
+pub const Z_STACK_LABEL: f32 = 1e-3;
 /// Layer that rain/snow/etc is rendered at
 pub const Z_WEATHER: f32 = 10.;
 
@@ -39,6 +54,8 @@ pub const Z_DEBUG: f32 = 100.;
 
 /// Height in pixels at which fonts are rastered (higher = sharper)
 pub const DEBUG_FONT_RENDER_SIZE: f32 = 32.;
+/// Height in pixels at which item stack labels are rastered
+pub const STACK_LABEL_FONT_RENDER_SIZE: f32 = 32.;
 
 /// Tiles around the starting point which can't be water terrain
 pub const TERRAIN_STARTING_RADIUS: i32 = 25;
@@ -60,10 +77,22 @@ pub const GROUND_ITEM_BOB_SPEED: f32 = 2.;
 /// How much a sprite is scaled up when being highlighted
 pub const HIGHLIGHT_SCALE: f32 = 1.2;
 
-/// How fast items roll down a hill. Tiles per second @ 1:1 gradient
-pub const ITEM_ROLL_SPEED: f32 = 5.;
-/// Minimum slope required for something to roll
+/// How fast items speed up rolling down a hill. Tiles per second^2 @ 1:1 gradient
+pub const ITEM_ROLL_ACCELERATION: f32 = 10.;
+/// Fastest an item can roll in tiles per second
+pub const ITEM_MAX_ROLL_SPEED: f32 = 8.;
+/// Minimum slope required for something to start rolling
 pub const ROLL_FRICTION: f32 = 0.1;
+/// Items slower than this come to rest unless the slope keeps them going. Tiles per second
+pub const ITEM_SETTLE_SPEED: f32 = 0.2;
+/// Fraction of speed kept when bouncing off machines & other items
+pub const ITEM_RESTITUTION: f32 = 0.5;
+/// Size of items when bumping into each other, in tiles
+pub const ITEM_COLLISION_RADIUS: f32 = 0.25;
+/// How quickly floating items are brought up to their drift speed, per second
+pub const WATER_DRAG: f32 = 2.;
+/// Fraction of the wind speed that floating items drift at
+pub const WATER_DRIFT: f32 = 0.2;
 
 /// How long fluff particles life in seconds
 pub const FLUFF_LIFETIME: f32 = 20.;
@@ -77,3 +106,14 @@ pub const WIND_TRANSITION_TIME: f32 = 5.;
 pub const WIND_CHANGES_PER_SECOND: f32 = 1. / 10.;
 /// Maximum strength of wind in tiles per second
 pub const MAX_WIND_SPEED: f32 = 3.;
+
+/// Tiles around a water mill which count towards the flow of its water
+pub const WATER_MILL_FLOW_RADIUS: i32 = 3;
+
+/// Seconds between samples of energy network stats
+pub const ENERGY_STATS_INTERVAL: f32 = 0.5;
+/// Number of energy network stat samples kept for the history graph
+pub const ENERGY_STATS_HISTORY: usize = 40;
+
+/// Directory that blueprints are saved to & loaded from
+pub const BLUEPRINT_DIR: &str = "blueprints";
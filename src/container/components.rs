@@ -1,6 +1,9 @@
-use bevy::{platform::collections::HashSet, prelude::*};
+use bevy::{ecs::query::QueryFilter, platform::collections::HashSet, prelude::*};
 
-use crate::{consts::Z_CONTAINED_ITEM, items::ItemType};
+use crate::{
+    consts::Z_CONTAINED_ITEM,
+    items::{Freshness, ItemType, Quantity},
+};
 
 /// Relationship for an item which is inside another item
 #[derive(Component)]
@@ -11,6 +14,51 @@ pub struct ContainedBy(pub Entity);
 #[derive(Component)]
 #[relationship_target(relationship = ContainedBy)]
 pub struct Contains(Vec<Entity>);
+impl Contains {
+    /// Move as much of `item` as will fit into stacks of the same type already in the container,
+    /// returning whether it's all gone
+    pub fn top_up<F: QueryFilter>(
+        &self,
+        item: Entity,
+        items: &mut Query<(&ItemType, &mut Quantity), F>,
+        freshness: &mut Query<&mut Freshness>,
+    ) -> bool {
+        top_up_stacks(&self.0, item, items, freshness)
+    }
+}
+
+/// Move as much of `item` as will fit into any of `stacks` of the same type, returning whether
+/// it's all gone
+pub fn top_up_stacks<F: QueryFilter>(
+    stacks: &[Entity],
+    item: Entity,
+    items: &mut Query<(&ItemType, &mut Quantity), F>,
+    freshness: &mut Query<&mut Freshness>,
+) -> bool {
+    let Ok((&item_type, _)) = items.get(item) else {
+        return false;
+    };
+
+    // Each stack that's topped up is either full or has emptied the item afterwards
+    while let Some(stack) = stacks.iter().copied().find(|stack| {
+        *stack != item
+            && items.get(*stack).is_ok_and(|(stack_type, quantity)| {
+                *stack_type == item_type && quantity.0 < item_type.max_stack_size()
+            })
+    }) {
+        let Ok([(_, mut stack_quantity), (_, mut quantity)]) = items.get_many_mut([stack, item])
+        else {
+            break;
+        };
+        let moved = stack_quantity.take_from(&mut quantity, item_type.max_stack_size());
+        Freshness::merge_stacks(freshness, stack, item, stack_quantity.0 - moved, moved);
+        if quantity.0 == 0 {
+            return true;
+        }
+    }
+
+    false
+}
 
 /// Marker for item that can contain other items
 #[derive(Component)]
@@ -20,6 +68,48 @@ pub struct Container;
 #[derive(Component)]
 pub struct ContainableItems(pub HashSet<ItemType>);
 
+/// Number of separate stacks a container can hold
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ContainerCapacity(pub usize);
+impl ContainerCapacity {
+    /// Whether there's a free slot for another stack
+    pub fn has_room(&self, contains: Option<&Contains>) -> bool {
+        self.free_slots(contains) > 0
+    }
+
+    /// Number of slots without a stack in them
+    pub fn free_slots(&self, contains: Option<&Contains>) -> usize {
+        self.0
+            .saturating_sub(contains.map_or(0, |contains| contains.iter().count()))
+    }
+}
+
+/// How fast perishable items spoil inside this container, relative to outside of one
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SpoilageRate(pub f32);
+
+#[derive(Bundle)]
+pub struct ContainerBundle {
+    container: Container,
+    capacity: ContainerCapacity,
+    spoilage_rate: SpoilageRate,
+    containables: ContainableItems,
+}
+impl ContainerBundle {
+    pub fn new(
+        capacity: usize,
+        spoilage_rate: f32,
+        containables: impl IntoIterator<Item = ItemType>,
+    ) -> Self {
+        Self {
+            container: Container,
+            capacity: ContainerCapacity(capacity),
+            spoilage_rate: SpoilageRate(spoilage_rate),
+            containables: ContainableItems(containables.into_iter().collect()),
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct ContainedBundle {
     parent: ChildOf,
@@ -36,3 +126,19 @@ impl ContainedBundle {
         }
     }
 }
+
+/// Top-level marker for the UI listing the contents of the held or targetted container
+#[derive(Component)]
+pub struct ContainerView(pub Entity);
+
+/// Text naming the container being viewed & how full it is
+#[derive(Component)]
+pub struct ContainerViewHeading;
+
+/// Container view button which takes a stack out of the container
+#[derive(Component)]
+pub struct ContainedItemButton(pub Entity);
+
+/// Text describing a stack in the viewed container
+#[derive(Component)]
+pub struct ContainedItemText(pub Entity);
@@ -13,12 +13,16 @@ impl Plugin for ContainerPlugin {
         app.add_systems(
             Update,
             (
-                contain_item,
-                uncontain_item
-                    // Target takes precedence
-                    .run_if(|targets: Query<(), With<TargettedBy>>| targets.is_empty()),
-            )
-                .run_if(key_just_pressed(KeyCode::KeyC)),
+                (
+                    contain_item,
+                    pour_container,
+                    uncontain_item
+                        // Target takes precedence
+                        .run_if(|targets: Query<(), With<TargettedBy>>| targets.is_empty()),
+                )
+                    .run_if(key_just_pressed(KeyCode::KeyC)),
+                (show_container_view, contained_item_button).chain(),
+            ),
         );
     }
 }
@@ -1,46 +1,143 @@
-use bevy::prelude::*;
+use bevy::{platform::collections::HashSet, prelude::*};
 
 use super::components::*;
 use crate::{
     ground_items::{GroundItem, GroundItemBundle},
-    items::ItemType,
+    inventory::{ContainerPanel, ContainerTransfer},
+    items::{Freshness, ItemType, Quantity},
     map::WorldPos,
     player::{HeldBy, Player, TargettedBy},
+    sprites::SpriteSheets,
 };
 
-/// Pick up a ground item and put it into the container
+/// Pick up a ground item and put it into the container, topping up stacks of the same item
+/// that are already in there first
 pub fn contain_item(
-    item: Single<(Entity, &ItemType), (With<GroundItem>, With<TargettedBy>)>,
-    container: Single<(Entity, &ContainableItems), (With<Container>, With<HeldBy>)>,
+    // Targetted containers are poured into instead
+    item: Single<Entity, (With<GroundItem>, With<TargettedBy>, Without<Container>)>,
+    container: Single<
+        (
+            Entity,
+            &ContainableItems,
+            &ContainerCapacity,
+            Option<&Contains>,
+        ),
+        (With<Container>, With<HeldBy>),
+    >,
+    mut items: Query<(&ItemType, &mut Quantity)>,
+    mut freshness: Query<&mut Freshness>,
     mut commands: Commands,
 ) {
-    if !container.1.0.contains(item.1) {
+    let (container, containables, capacity, contains) = *container;
+    let item = *item;
+    let item_type = *items.get(item).expect("Ground items have a type").0;
+
+    if !containables.0.contains(&item_type) {
         // Item can't be put in this container
         return;
     }
 
-    info!("Containing item {:?}", item.0);
+    // Merge into existing stacks first
+    if contains.is_some_and(|contains| contains.top_up(item, &mut items, &mut freshness)) {
+        info!("Merged item {:?} into container", item);
+        commands.entity(item).despawn();
+        return;
+    }
+
+    if !capacity.has_room(contains) {
+        info!("Container is full");
+        return;
+    }
+
+    info!("Containing item {:?}", item);
 
     commands
-        .entity(item.0)
+        .entity(item)
         // Remove ground related components
         .remove::<GroundItemBundle>()
         // Add containing related components
-        .insert(ContainedBundle::new(container.0));
+        .insert(ContainedBundle::new(container));
+}
+
+/// Pour the contents of the held container into the targetted one, as far as they'll fit
+pub fn pour_container(
+    target: Single<
+        (
+            Entity,
+            &ContainableItems,
+            &ContainerCapacity,
+            Option<&Contains>,
+        ),
+        (With<Container>, With<GroundItem>, With<TargettedBy>),
+    >,
+    held: Single<&Contains, (With<Container>, With<HeldBy>)>,
+    mut items: Query<(&ItemType, &mut Quantity), With<ContainedBy>>,
+    mut freshness: Query<&mut Freshness>,
+    mut commands: Commands,
+) {
+    let (target, containables, capacity, target_contains) = *target;
+
+    let mut free_slots = capacity.free_slots(target_contains);
+    // Stacks poured in by this call, which won't show up in the target's contents until the
+    // commands are applied
+    let mut poured = vec![];
+    for item in held.iter() {
+        let item_type = *items.get(item).expect("Contained items have a type").0;
+        if !containables.0.contains(&item_type) {
+            // Item can't be put in the target container
+            continue;
+        }
+
+        // Merge into existing stacks first
+        if target_contains.is_some_and(|contains| contains.top_up(item, &mut items, &mut freshness))
+            || top_up_stacks(&poured, item, &mut items, &mut freshness)
+        {
+            commands.entity(item).despawn();
+            continue;
+        }
+
+        if free_slots == 0 {
+            // Anything left stays in the held container
+            continue;
+        }
+        free_slots -= 1;
+
+        info!("Pouring {:?} into container {:?}", item_type, target);
+        commands.entity(item).insert(ContainedBundle::new(target));
+        poured.push(item);
+    }
 }
 
-/// Take an item out of the held container and put it on the ground
+/// Take a single item out of the held container and put it on the ground
 pub fn uncontain_item(
-    container: Single<(Entity, &Contains), (With<Container>, With<HeldBy>)>,
+    container: Single<&Contains, (With<Container>, With<HeldBy>)>,
     player_pos: Single<&WorldPos, With<Player>>,
+    mut items: Query<(&ItemType, &mut Quantity), With<ContainedBy>>,
+    freshness: Query<&Freshness>,
+    sprite_sheets: Res<SpriteSheets>,
     mut commands: Commands,
 ) {
     let item = container
-        .1
         .iter()
         .next()
         .expect("System only runs when there are contained items");
 
+    if let Ok((item_type, mut quantity)) = items.get_mut(item)
+        && quantity.0 > 1
+    {
+        // Split one off the stack
+        info!("Un-containing one {:?}", item_type);
+        quantity.0 -= 1;
+        let single = item_type.spawn_stack(&mut commands, &sprite_sheets, 1);
+        commands
+            .entity(single)
+            .insert(GroundItemBundle::new(*player_pos));
+        if let Ok(freshness) = freshness.get(item) {
+            commands.entity(single).insert(*freshness);
+        }
+        return;
+    }
+
     info!("Un-containing item {:?}", item);
 
     commands
@@ -48,3 +145,143 @@ pub fn uncontain_item(
         .remove::<ContainedBundle>()
         .insert(GroundItemBundle::new(*player_pos));
 }
+
+/// List the contents of the held container, or the targetted one if no container is held. The list
+/// sits above the hotbar, or in the inventory window's container panel while that's open.
+pub fn show_container_view(
+    held: Option<Single<Entity, (With<Container>, With<HeldBy>)>>,
+    targetted: Option<Single<Entity, (With<Container>, With<TargettedBy>)>>,
+    containers: Query<(&ItemType, &ContainerCapacity, Option<&Contains>)>,
+    items: Query<(&ItemType, &Quantity), With<ContainedBy>>,
+    view: Option<Single<(Entity, &ContainerView, Has<ChildOf>)>>,
+    panel: Option<Single<Entity, With<ContainerPanel>>>,
+    mut heading: Option<Single<&mut Text, With<ContainerViewHeading>>>,
+    buttons: Query<(Entity, &ContainedItemButton)>,
+    texts: Query<(&mut Text, &ContainedItemText), Without<ContainerViewHeading>>,
+    mut commands: Commands,
+) {
+    let container = held
+        .map(|held| *held)
+        .or(targetted.map(|targetted| *targetted));
+
+    // Swap the view over when looking at a different container, or moving in/out of the panel
+    let view = match (view.map(|view| *view), container) {
+        (Some((view, viewed, docked)), Some(container))
+            if viewed.0 == container && docked == panel.is_some() =>
+        {
+            view
+        }
+        (view, container) => {
+            if let Some((view, ..)) = view {
+                commands.entity(view).despawn();
+            }
+            if let Some(container) = container {
+                let view = commands
+                    .spawn((
+                        ContainerView(container),
+                        children![(ContainerViewHeading, Text::default())],
+                    ))
+                    .id();
+                if let Some(panel) = panel {
+                    commands.entity(view).insert((
+                        ChildOf(*panel),
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            ..Default::default()
+                        },
+                    ));
+                } else {
+                    commands.entity(view).insert(Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        position_type: PositionType::Absolute,
+                        // Just above the hotbar
+                        bottom: px(60),
+                        width: percent(100),
+                        ..Default::default()
+                    });
+                }
+            }
+            return;
+        }
+    };
+    let Some(container) = container else {
+        return;
+    };
+    let (container_type, capacity, contains) = containers
+        .get(container)
+        .expect("Viewed container should have a capacity");
+
+    let contained = contains
+        .map(|contains| contains.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if let Some(heading) = &mut heading {
+        heading.0 = format!("{:?} ({}/{})", container_type, contained.len(), capacity.0);
+    }
+
+    // Remove buttons for items which have left the container
+    let mut existing = HashSet::new();
+    for (button, item) in buttons {
+        if contained.contains(&item.0) {
+            existing.insert(item.0);
+        } else {
+            commands.entity(button).despawn();
+        }
+    }
+
+    // Add buttons for new items
+    for item in contained {
+        if existing.contains(&item) {
+            continue;
+        }
+
+        commands.entity(view).with_child((
+            Button,
+            ContainedItemButton(item),
+            Node {
+                border: UiRect::all(px(2)),
+                margin: UiRect::all(px(2)),
+                padding: UiRect::all(px(4)),
+                ..Default::default()
+            },
+            BorderColor::all(Color::WHITE),
+            BackgroundColor(Color::BLACK),
+            children![(
+                Text::default(),
+                TextFont {
+                    font_size: 14.,
+                    ..Default::default()
+                },
+                ContainedItemText(item),
+            )],
+        ));
+    }
+
+    // Update text with the latest stack sizes
+    for (mut text, item) in texts {
+        if let Ok((item_type, quantity)) = items.get(item.0) {
+            text.0 = item_type.stack_name(quantity.0);
+        }
+    }
+}
+
+/// Interaction with the viewed container's contents, clicking a stack takes it out into the
+/// player's inventory
+pub fn contained_item_button(
+    buttons: Query<
+        (&Interaction, &mut BackgroundColor, &ContainedItemButton),
+        Changed<Interaction>,
+    >,
+    mut writer: MessageWriter<ContainerTransfer>,
+) {
+    for (interaction, mut bg_color, item) in buttons {
+        match interaction {
+            Interaction::Pressed => {
+                writer.write(ContainerTransfer::Out(item.0));
+            }
+            Interaction::Hovered => *bg_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            Interaction::None => *bg_color = BackgroundColor(Color::BLACK),
+        }
+    }
+}
@@ -29,7 +29,7 @@ pub struct CraftRecipe(pub Recipe);
 #[derive(Debug)]
 pub enum FailedCraftReason {
     NotEnoughResources,
-    HoldingItem,
+    InventoryFull,
 }
 
 /// Event thrown when a craft is attempted but fails
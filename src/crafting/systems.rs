@@ -3,10 +3,11 @@ use bevy::prelude::*;
 use super::components::*;
 use crate::{
     consts::PLAYER_REACH,
+    inventory::{Inventory, InventorySlot, Stowing},
     knowledge::Unlocked,
     map::{TilePos, WorldPos},
-    player::{HeldItemBundle, Holding, Player},
-    sprites::{GetSprite, SpriteSheets},
+    player::{Holding, Player},
+    sprites::SpriteSheets,
     village::{ResourceStockpile, Stockpiles, VillageCentre},
 };
 
@@ -112,24 +113,29 @@ pub fn crafting_button(
 pub fn try_craft_recipes(
     mut reader: MessageReader<CraftRecipe>,
     mut stockpiles: Stockpiles<&mut ResourceStockpile>,
-    player: Single<(Entity, Has<Holding>), (With<Player>, Without<ResourceStockpile>)>,
+    player: Single<
+        (Entity, &Inventory, Option<&Holding>, Option<&Stowing>),
+        (With<Player>, Without<ResourceStockpile>),
+    >,
+    stowed_items: Query<&InventorySlot>,
     sprite_sheets: Res<SpriteSheets>,
     mut commands: Commands,
 ) {
-    let (player, held_item) = *player;
+    let (player, inventory, holding, stowing) = *player;
+    let mut slots = inventory.slots(holding, stowing, &stowed_items);
 
     // TODO: Might need to just process 1 per frame here
     for CraftRecipe(recipe) in reader.read() {
         info!("Attempting to craft: {:?}", recipe.product);
 
-        if held_item {
+        // Crafted item is given to the player, so they need somewhere to put it
+        let Some(slot) = inventory.free_slot(&slots) else {
             commands.trigger(FailedCraft {
                 recipe: recipe.clone(),
-                reason: FailedCraftReason::HoldingItem,
+                reason: FailedCraftReason::InventoryFull,
             });
-            // Crafted item is given to the player, so they need to be able to hold it
             continue;
-        }
+        };
 
         // Check if we've got enough resources
         if recipe.reqs.iter().all(|(res_type, amount)| {
@@ -148,14 +154,10 @@ pub fn try_craft_recipes(
                 stockpile.0 -= *amount as f32;
             }
 
-            // Give item to player
-            let mut entity_commands = commands.spawn((HeldItemBundle::new(player), recipe.product));
-            recipe.product.add_extra_components(&mut entity_commands);
-            let entity = entity_commands.id();
-
-            recipe
-                .product
-                .spawn_sprite(&mut commands, &sprite_sheets, Some(entity));
+            // Give item to player, straight into their hands if the selected slot is free
+            let entity = recipe.product.spawn_stack(&mut commands, &sprite_sheets, 1);
+            inventory.put_in_slot(&mut commands.entity(entity), player, slot);
+            slots[slot] = Some(entity);
         } else {
             // Not enough resources
             commands.trigger(FailedCraft {
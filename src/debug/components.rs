@@ -36,3 +36,9 @@ pub struct ElectricityDebugLUT(pub HashMap<TilePos, Entity>);
 /// machines. When `false` those entities will be despawned.
 #[derive(Resource, Default, PartialEq, Eq)]
 pub struct ElectricityDebugEnabled(pub bool);
+
+/// Marker component for the UI panel summarising each energy network.
+///
+/// Shown alongside the electricity debug labels.
+#[derive(Component)]
+pub struct NetworkSummaryPanel;
@@ -28,8 +28,9 @@ impl Plugin for DebugPlugin {
                         .chain()
                         .run_if(resource_equals(GradientArrowsEnabled(true))),
                     despawn_gradient_arrows.run_if(resource_equals(GradientArrowsEnabled(false))),
-                    update_electricity_debug.run_if(resource_equals(ElectricityDebugEnabled(true))),
-                    despawn_electricity_debug
+                    (update_electricity_debug, update_network_summary)
+                        .run_if(resource_equals(ElectricityDebugEnabled(true))),
+                    (despawn_electricity_debug, despawn_network_summary)
                         .run_if(resource_equals(ElectricityDebugEnabled(false))),
                 ),
             );
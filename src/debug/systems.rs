@@ -5,7 +5,10 @@ use bevy::{math::ops::atan2, prelude::*};
 use super::components::*;
 use crate::{
     consts::{DEBUG_FONT_RENDER_SIZE, Z_DEBUG},
-    machines::{EnergyNetworks, Machine, Placed, PowerConsumption, PowerProduction},
+    machines::{
+        BatteryCapacity, EnergyNetworks, Machine, NetworkTotals, Placed, PowerPriority,
+        PowerProduction, StoredEnergy,
+    },
     map::{ChunkPos, GradientData, TilePos},
     sprites::{GetSprite, ResourceSprite, SpriteSheets},
 };
@@ -113,12 +116,17 @@ pub fn update_electricity_debug(
     mut label_lut: ResMut<ElectricityDebugLUT>,
     energy_networks: Res<EnergyNetworks>,
     machines: Query<
-        (&TilePos, Has<PowerConsumption>, Option<&PowerProduction>),
+        (
+            &TilePos,
+            Option<&PowerPriority>,
+            Option<&PowerProduction>,
+            Option<(&StoredEnergy, &BatteryCapacity)>,
+        ),
         (With<Placed>, With<Machine>),
     >,
     mut commands: Commands,
 ) {
-    for (tile_pos, consumer, production) in &machines {
+    for (tile_pos, priority, production, battery) in &machines {
         // Network index
         let index = if let Some(idx) = energy_networks.membership.get(tile_pos) {
             format!("{idx}")
@@ -126,13 +134,24 @@ pub fn update_electricity_debug(
             "-".to_string()
         };
 
-        // Power consumption for consumers
-        let requested =
-            if consumer && let Some(requested) = energy_networks.power_demands.get(tile_pos) {
-                format!("{requested:.2}")
-            } else {
-                "-".to_string()
-            };
+        // Power consumption and priority for consumers
+        let requested = if let Some(priority) = priority
+            && let Some(requested) = energy_networks.power_demands.get(tile_pos)
+        {
+            format!("{:.2} ({priority:?})", requested.total())
+        } else {
+            "-".to_string()
+        };
+
+        // Breakdown of the requested power into idle / active / load draw
+        let breakdown = if let Some(requested) = energy_networks.power_demands.get(tile_pos) {
+            format!(
+                "{:.2}/{:.2}/{:.2}",
+                requested.idle, requested.active, requested.load
+            )
+        } else {
+            "-".to_string()
+        };
 
         // Power production for producers
         let production = if let Some(production) = production {
@@ -141,9 +160,22 @@ pub fn update_electricity_debug(
             "-".to_string()
         };
 
-        // Actual power supplied by network for consumers
+        // Actual power supplied by network for consumers, flagging ones that are starved
+        let starved = energy_networks.is_starved(tile_pos);
         let supplied = if let Some(supplied) = energy_networks.power_provided.get(tile_pos) {
-            format!("{:.2}", supplied)
+            format!("{:.2}{}", supplied, if starved { " !" } else { "" })
+        } else {
+            "-".to_string()
+        };
+
+        // Stored charge and current charge rate for batteries
+        let stored = if let Some((stored, capacity)) = battery {
+            let flow = energy_networks
+                .battery_flow
+                .get(tile_pos)
+                .copied()
+                .unwrap_or(0.);
+            format!("{:.1}/{:.0} ({flow:+.2})", stored.0, capacity.0)
         } else {
             "-".to_string()
         };
@@ -152,7 +184,9 @@ pub fn update_electricity_debug(
             format!("N: {index}"),
             format!("P: {production}"),
             format!("R: {requested}"),
+            format!("D: {breakdown}"),
             format!("S: {supplied}"),
+            format!("B: {stored}"),
         ];
 
         // Get the label entity, or spawn one if it doesn't exist
@@ -190,7 +224,12 @@ pub fn update_electricity_debug(
                     font_size: DEBUG_FONT_RENDER_SIZE,
                     ..Default::default()
                 },
-                TextColor(Color::srgb(1., 0., 0.)),
+                // Starved machines stand out from the rest
+                TextColor(if starved {
+                    Color::srgb(1., 1., 0.)
+                } else {
+                    Color::srgb(1., 0., 0.)
+                }),
                 Transform::from_scale(
                     Vec2::splat(1. / (DEBUG_FONT_RENDER_SIZE * debug_lines.len() as f32))
                         .extend(1.),
@@ -199,3 +238,67 @@ pub fn update_electricity_debug(
         });
     }
 }
+
+/// Show a summary of each energy network's production & demand breakdown in the corner of the
+/// screen.
+pub fn update_network_summary(
+    panel: Option<Single<&mut Text, With<NetworkSummaryPanel>>>,
+    energy_networks: Res<EnergyNetworks>,
+    mut commands: Commands,
+) {
+    let totals = energy_networks.network_totals();
+
+    let mut networks = energy_networks.networks.keys().copied().collect::<Vec<_>>();
+    networks.sort();
+
+    let mut lines = vec!["Network: production | idle/active/load | battery".to_string()];
+    lines.extend(networks.into_iter().map(|network| {
+        let production = energy_networks
+            .power_available
+            .get(&network)
+            .copied()
+            .unwrap_or(0.);
+        let NetworkTotals {
+            demand,
+            battery_flow,
+            ..
+        } = totals.get(&network).copied().unwrap_or_default();
+
+        format!(
+            "{network}: {production:.2} | {:.2}/{:.2}/{:.2} | {battery_flow:+.2}",
+            demand.idle, demand.active, demand.load
+        )
+    }));
+    let text = lines.join("\n");
+
+    if let Some(mut panel) = panel {
+        panel.0 = text;
+    } else {
+        commands.spawn((
+            NetworkSummaryPanel,
+            Text::new(text),
+            TextFont {
+                font_size: 14.,
+                ..Default::default()
+            },
+            Node {
+                position_type: PositionType::Absolute,
+                top: px(10),
+                // The energy stats panel lives in the top right
+                left: px(10),
+                ..Default::default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        ));
+    }
+}
+
+/// Despawn the network summary panel.
+pub fn despawn_network_summary(
+    panel: Option<Single<Entity, With<NetworkSummaryPanel>>>,
+    mut commands: Commands,
+) {
+    if let Some(panel) = panel {
+        commands.entity(*panel).despawn();
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{consts::ENERGY_STATS_INTERVAL, machines::PowerDemand};
+
+/// Snapshot of an energy network's state
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NetworkSample {
+    /// Power produced by generators
+    pub production: f32,
+    /// Power requested by consumers
+    pub demand: PowerDemand,
+    /// Fraction of the demand that was supplied, 0 - 1
+    pub satisfaction: f32,
+    /// Power flowing into (positive) or out of (negative) batteries
+    pub battery_flow: f32,
+    /// Number of placed machines producing power
+    pub producers: usize,
+    /// Number of placed machines consuming power
+    pub consumers: usize,
+}
+
+/// Recent history of each energy network, keyed by network ID. Newest samples are at the back.
+#[derive(Resource)]
+pub struct EnergyStats {
+    pub history: HashMap<usize, VecDeque<NetworkSample>>,
+    pub timer: Timer,
+}
+impl Default for EnergyStats {
+    fn default() -> Self {
+        Self {
+            history: HashMap::new(),
+            timer: Timer::from_seconds(ENERGY_STATS_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Resource controlling whether the energy stats panel is shown
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnergyStatsEnabled(pub bool);
+
+/// Network selected in the stats panel, whose tiles are highlighted in the world
+#[derive(Resource, Default)]
+pub struct HighlightedNetwork(pub Option<usize>);
+
+/// Top-level marker for the energy stats panel
+#[derive(Component)]
+pub struct EnergyStatsPanel;
+
+/// Clickable panel row for a single network
+#[derive(Component)]
+pub struct NetworkRow(pub usize);
+
+/// Text describing a network's latest sample
+#[derive(Component)]
+pub struct NetworkRowText(pub usize);
+
+/// One bar in a network's history graph, oldest first
+#[derive(Component)]
+pub struct HistoryBar {
+    pub network: usize,
+    pub index: usize,
+}
@@ -0,0 +1,29 @@
+mod components;
+mod systems;
+
+use bevy::prelude::*;
+pub use components::*;
+use systems::*;
+
+use crate::utils::run_if::key_just_pressed;
+
+pub struct EnergyStatsPlugin;
+impl Plugin for EnergyStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnergyStats>()
+            .init_resource::<EnergyStatsEnabled>()
+            .init_resource::<HighlightedNetwork>()
+            .add_systems(
+                Update,
+                (
+                    toggle_energy_stats.run_if(key_just_pressed(KeyCode::KeyN)),
+                    record_energy_stats,
+                    (show_energy_stats, select_network, draw_network_highlight)
+                        .chain()
+                        .run_if(resource_equals(EnergyStatsEnabled(true))),
+                    hide_energy_stats.run_if(resource_equals(EnergyStatsEnabled(false))),
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,298 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use super::components::*;
+use crate::{
+    consts::{ENERGY_STATS_HISTORY, Z_DEBUG},
+    machines::{
+        EnergyNetworks, NetworkTotals, Placed, PowerConsumption, PowerDemand, PowerProduction,
+    },
+    map::TilePos,
+};
+
+/// Toggle whether the energy stats panel is shown.
+pub fn toggle_energy_stats(mut enabled: ResMut<EnergyStatsEnabled>) {
+    enabled.0 ^= true;
+    if enabled.0 {
+        info!("Showing energy stats");
+    } else {
+        info!("Hiding energy stats");
+    }
+}
+
+/// Take a sample of every energy network at regular intervals
+pub fn record_energy_stats(
+    mut stats: ResMut<EnergyStats>,
+    energy_networks: Res<EnergyNetworks>,
+    machines: Query<(&TilePos, Has<PowerProduction>, Has<PowerConsumption>), With<Placed>>,
+    timer: Res<Time>,
+) {
+    if !stats.timer.tick(timer.delta()).just_finished() {
+        return;
+    }
+
+    let mut samples = energy_networks
+        .networks
+        .keys()
+        .map(|network| {
+            let production = energy_networks
+                .power_available
+                .get(network)
+                .copied()
+                .unwrap_or(0.);
+            (
+                *network,
+                NetworkSample {
+                    production,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Count up the machines in each network
+    for (tile_pos, producer, consumer) in machines {
+        if let Some(network) = energy_networks.membership.get(tile_pos)
+            && let Some(sample) = samples.get_mut(network)
+        {
+            sample.producers += producer as usize;
+            sample.consumers += consumer as usize;
+        }
+    }
+
+    // Fill in demand, supply & battery flow in each network
+    let totals = energy_networks.network_totals();
+    for (network, sample) in &mut samples {
+        let NetworkTotals {
+            demand,
+            provided,
+            battery_flow,
+        } = totals.get(network).copied().unwrap_or_default();
+        sample.demand = demand;
+        sample.battery_flow = battery_flow;
+        sample.satisfaction = if demand.total() == 0. {
+            1.
+        } else {
+            (provided / demand.total()).min(1.)
+        };
+    }
+
+    // Forget networks which no longer exist
+    stats
+        .history
+        .retain(|network, _| samples.contains_key(network));
+
+    for (network, sample) in samples {
+        let history = stats.history.entry(network).or_default();
+        history.push_back(sample);
+        if history.len() > ENERGY_STATS_HISTORY {
+            history.pop_front();
+        }
+    }
+}
+
+/// Describe a network's latest sample
+fn describe_network(network: usize, sample: &NetworkSample) -> String {
+    let PowerDemand { idle, active, load } = sample.demand;
+    format!(
+        "Network {network}: {:.0}% satisfied\n\
+         Production: {:.2} ({} producers)\n\
+         Demand: {:.2} ({} consumers, {idle:.2}/{active:.2}/{load:.2} idle/active/load)\n\
+         Battery: {:+.2}",
+        sample.satisfaction * 100.,
+        sample.production,
+        sample.producers,
+        sample.demand.total(),
+        sample.consumers,
+        sample.battery_flow,
+    )
+}
+
+/// Colour of a history bar for a given satisfaction
+fn satisfaction_colour(satisfaction: f32) -> Color {
+    if satisfaction >= 1. {
+        Color::srgb(0.2, 0.8, 0.2)
+    } else if satisfaction >= 0.5 {
+        Color::srgb(0.9, 0.8, 0.1)
+    } else {
+        Color::srgb(0.9, 0.2, 0.1)
+    }
+}
+
+/// Spawn the energy stats panel and keep it in sync with the recorded stats
+pub fn show_energy_stats(
+    panel: Option<Single<Entity, With<EnergyStatsPanel>>>,
+    stats: Res<EnergyStats>,
+    mut highlighted: ResMut<HighlightedNetwork>,
+    rows: Query<(Entity, &NetworkRow, &mut BorderColor)>,
+    texts: Query<(&mut Text, &NetworkRowText)>,
+    bars: Query<(&mut Node, &mut BackgroundColor, &HistoryBar), Without<NetworkRow>>,
+    mut commands: Commands,
+) {
+    let Some(panel) = panel else {
+        commands.spawn((
+            EnergyStatsPanel,
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                top: px(10),
+                right: px(10),
+                ..Default::default()
+            },
+        ));
+        return;
+    };
+
+    // Stop highlighting networks that are gone
+    if let Some(network) = highlighted.0
+        && !stats.history.contains_key(&network)
+    {
+        highlighted.0 = None;
+    }
+
+    // Remove rows for networks which no longer exist
+    let mut existing = HashMap::new();
+    for (row, network, mut border) in rows {
+        if stats.history.contains_key(&network.0) {
+            *border = BorderColor::all(if highlighted.0 == Some(network.0) {
+                Color::srgb(1., 1., 0.)
+            } else {
+                Color::WHITE
+            });
+            existing.insert(network.0, row);
+        } else {
+            commands.entity(row).despawn();
+        }
+    }
+
+    // Add rows for new networks
+    let mut networks = stats.history.keys().copied().collect::<Vec<_>>();
+    networks.sort();
+    for network in networks {
+        if existing.contains_key(&network) {
+            continue;
+        }
+
+        commands.entity(*panel).with_child((
+            Button,
+            NetworkRow(network),
+            Node {
+                flex_direction: FlexDirection::Column,
+                border: UiRect::all(px(2)),
+                margin: UiRect::all(px(2)),
+                padding: UiRect::all(px(4)),
+                ..Default::default()
+            },
+            BorderColor::all(Color::WHITE),
+            BackgroundColor(Color::BLACK),
+            children![
+                (
+                    Text::default(),
+                    TextFont {
+                        font_size: 14.,
+                        ..Default::default()
+                    },
+                    NetworkRowText(network),
+                ),
+                (
+                    // History graph, bars grow upwards from the bottom
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::FlexEnd,
+                        height: px(24),
+                        margin: UiRect::top(px(4)),
+                        ..Default::default()
+                    },
+                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    Children::spawn(SpawnIter((0..ENERGY_STATS_HISTORY).map(move |index| {
+                        (
+                            Node {
+                                width: px(4),
+                                height: percent(0),
+                                ..Default::default()
+                            },
+                            BackgroundColor(Color::NONE),
+                            HistoryBar { network, index },
+                        )
+                    }))),
+                ),
+            ],
+        ));
+    }
+
+    // Update text with the latest sample
+    for (mut text, network) in texts {
+        if let Some(sample) = stats.history.get(&network.0).and_then(|h| h.back()) {
+            text.0 = describe_network(network.0, sample);
+        }
+    }
+
+    // Update graph bars, lining the newest sample up with the right-hand edge
+    for (mut node, mut colour, bar) in bars {
+        let Some(history) = stats.history.get(&bar.network) else {
+            continue;
+        };
+        let offset = ENERGY_STATS_HISTORY - history.len();
+        if let Some(sample) = bar.index.checked_sub(offset).and_then(|i| history.get(i)) {
+            node.height = percent(sample.satisfaction.max(0.05) * 100.);
+            *colour = BackgroundColor(satisfaction_colour(sample.satisfaction));
+        } else {
+            node.height = percent(0);
+            *colour = BackgroundColor(Color::NONE);
+        }
+    }
+}
+
+/// Despawn the energy stats panel & clear any highlight.
+pub fn hide_energy_stats(
+    panel: Option<Single<Entity, With<EnergyStatsPanel>>>,
+    mut highlighted: ResMut<HighlightedNetwork>,
+    mut commands: Commands,
+) {
+    if let Some(panel) = panel {
+        commands.entity(*panel).despawn();
+    }
+    highlighted.0 = None;
+}
+
+/// Interaction with network rows, clicking one toggles its highlight
+pub fn select_network(
+    rows: Query<(&Interaction, &mut BackgroundColor, &NetworkRow), Changed<Interaction>>,
+    mut highlighted: ResMut<HighlightedNetwork>,
+) {
+    for (interaction, mut bg_color, network) in rows {
+        match interaction {
+            Interaction::Pressed => {
+                highlighted.0 = if highlighted.0 == Some(network.0) {
+                    None
+                } else {
+                    Some(network.0)
+                };
+            }
+            Interaction::Hovered => *bg_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            Interaction::None => *bg_color = BackgroundColor(Color::BLACK),
+        }
+    }
+}
+
+/// Outline each tile of the highlighted network
+pub fn draw_network_highlight(
+    highlighted: Res<HighlightedNetwork>,
+    energy_networks: Res<EnergyNetworks>,
+    mut gizmos: Gizmos,
+) {
+    let Some(tiles) = highlighted
+        .0
+        .and_then(|network| energy_networks.networks.get(&network))
+    else {
+        return;
+    };
+
+    for tile_pos in tiles {
+        gizmos.rect(
+            tile_pos.as_world_pos().0.extend(Z_DEBUG),
+            Vec2::splat(0.9),
+            Color::srgb(1., 1., 0.),
+        );
+    }
+}
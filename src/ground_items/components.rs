@@ -1,11 +1,11 @@
 use std::f32;
 
-use bevy::prelude::*;
+use bevy::{platform::collections::HashMap, prelude::*};
 
 use crate::{
     consts::{GROUND_ITEM_BOB_SPEED, Z_GROUND_ITEM},
     items::ItemType,
-    map::WorldPos,
+    map::{TilePos, WorldPos},
     player::Targettable,
 };
 
@@ -22,6 +22,10 @@ pub struct AnimationTime(pub f32);
 #[require(AnimationTime)]
 pub struct AnimationCycleTime(pub f32);
 
+/// Speed & direction an item is rolling in, tiles per second
+#[derive(Component, Default, Debug)]
+pub struct Velocity(pub Vec2);
+
 #[derive(Bundle)]
 pub struct GroundItemBundle {
     ground_marker: GroundItem,
@@ -30,6 +34,7 @@ pub struct GroundItemBundle {
     animation_time: AnimationTime,
     animation_total: AnimationCycleTime,
     transform: Transform,
+    velocity: Velocity,
 }
 impl GroundItemBundle {
     pub fn new(world_pos: &WorldPos) -> Self {
@@ -40,6 +45,7 @@ impl GroundItemBundle {
             animation_time: AnimationTime(0.),
             animation_total: AnimationCycleTime(GROUND_ITEM_BOB_SPEED),
             transform: world_pos.as_transform(Z_GROUND_ITEM),
+            velocity: Velocity::default(),
         }
     }
 }
@@ -52,3 +58,81 @@ pub struct ItemRolled {
     pub item: ItemType,
     pub distance: f32,
 }
+
+/// Spatial lookup for items on the ground, bucketed by the tile they're over. Kept up to date as
+/// items are dropped, picked up & roll around.
+#[derive(Resource, Default)]
+pub struct GroundItemIndex {
+    tiles: HashMap<TilePos, Vec<Entity>>,
+    positions: HashMap<Entity, WorldPos>,
+}
+impl GroundItemIndex {
+    /// Tile an item at this position is over
+    pub fn tile_of(world_pos: &WorldPos) -> TilePos {
+        // NOTE: +0.5 so items are bucketed by the tile centre they're closest to, instead of the
+        // origin corner
+        (world_pos + Vec2::splat(0.5)).tile()
+    }
+
+    /// Start tracking an item, or move it if it's already tracked
+    pub fn insert(&mut self, item: Entity, world_pos: WorldPos) {
+        if let Some(old_pos) = self.positions.insert(item, world_pos) {
+            let (old_tile, new_tile) = (Self::tile_of(&old_pos), Self::tile_of(&world_pos));
+            if old_tile == new_tile {
+                // Still over the same tile
+                return;
+            }
+            self.remove_from_tile(item, &old_tile);
+        }
+
+        self.tiles
+            .entry(Self::tile_of(&world_pos))
+            .or_default()
+            .push(item);
+    }
+
+    /// Stop tracking an item
+    pub fn remove(&mut self, item: Entity) {
+        if let Some(world_pos) = self.positions.remove(&item) {
+            self.remove_from_tile(item, &Self::tile_of(&world_pos));
+        }
+    }
+
+    fn remove_from_tile(&mut self, item: Entity, tile_pos: &TilePos) {
+        if let Some(items) = self.tiles.get_mut(tile_pos) {
+            items.retain(|other| *other != item);
+            if items.is_empty() {
+                self.tiles.remove(tile_pos);
+            }
+        }
+    }
+
+    /// Items over a tile, in the order they arrived
+    pub fn at_tile<'a>(
+        &'a self,
+        tile_pos: &TilePos,
+    ) -> impl Iterator<Item = (Entity, WorldPos)> + use<'a> {
+        self.tiles
+            .get(tile_pos)
+            .into_iter()
+            .flatten()
+            .map(|item| (*item, self.positions[item]))
+    }
+
+    /// Items within `radius` of `centre`, along with their squared distance from it
+    pub fn in_radius(
+        &self,
+        centre: &WorldPos,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, WorldPos, f32)> {
+        let min = Self::tile_of(&(centre - Vec2::splat(radius))).0;
+        let max = Self::tile_of(&(centre + Vec2::splat(radius))).0;
+        let centre = *centre;
+
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| TilePos(IVec2::new(x, y))))
+            .flat_map(move |tile_pos| self.at_tile(&tile_pos))
+            .map(move |(item, world_pos)| (item, world_pos, world_pos.0.distance_squared(centre.0)))
+            .filter(move |(_, _, distance2)| *distance2 <= radius.powi(2))
+    }
+}
@@ -5,19 +5,40 @@ use bevy::prelude::*;
 pub use components::*;
 use systems::*;
 
-use crate::utils::run_if::{empty_hands, key_just_pressed};
+use crate::utils::run_if::{cursor_over_target, empty_hands, key_just_pressed, mouse_just_pressed};
 
 pub struct GroundItemPlugin;
 impl Plugin for GroundItemPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                animate_items,
-                (drop_item, pickup_item.run_if(empty_hands))
-                    .run_if(key_just_pressed(KeyCode::KeyE)),
-                roll_items,
-            ),
-        );
+        app.init_resource::<GroundItemIndex>()
+            .add_observer(index_ground_item)
+            .add_observer(unindex_ground_item)
+            .add_systems(
+                Update,
+                (
+                    animate_items,
+                    (drop_item, pickup_item.run_if(empty_hands))
+                        .run_if(key_just_pressed(KeyCode::KeyE)),
+                    pickup_item.run_if(
+                        mouse_just_pressed(MouseButton::Left)
+                            .and(empty_hands)
+                            .and(cursor_over_target),
+                    ),
+                    drop_one.run_if(key_just_pressed(KeyCode::KeyQ)),
+                    // Click on a matching item to add it to the held stack
+                    pickup_into_stack.run_if(
+                        mouse_just_pressed(MouseButton::Left)
+                            .and(not(empty_hands))
+                            .and(cursor_over_target),
+                    ),
+                    (
+                        roll_items,
+                        collide_items,
+                        update_ground_item_index,
+                        merge_ground_stacks,
+                    )
+                        .chain(),
+                ),
+            );
     }
 }
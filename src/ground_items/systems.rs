@@ -1,13 +1,19 @@
 use std::f32;
 
-use bevy::prelude::*;
+use bevy::{platform::collections::HashSet, prelude::*};
 
 use super::components::*;
 use crate::{
-    consts::{GROUND_ITEM_BOB_HEIGHT, ITEM_ROLL_SPEED, ROLL_FRICTION},
-    items::ItemType,
-    map::{Chunks, GradientData, WorldPos},
-    player::{HeldItemBundle, Holding, Player, TargettedBy},
+    consts::{
+        GROUND_ITEM_BOB_HEIGHT, ITEM_COLLISION_RADIUS, ITEM_MAX_ROLL_SPEED, ITEM_RESTITUTION,
+        ITEM_ROLL_ACCELERATION, ITEM_SETTLE_SPEED, ROLL_FRICTION, WATER_DRAG, WATER_DRIFT,
+    },
+    items::{Freshness, ItemType, Quantity},
+    machines::{Face, Machines, Placed, Ports},
+    map::{Chunks, GradientData, TerrainData, TilePos, WorldPos},
+    player::{HeldBy, HeldItemBundle, Holding, Player, TargettedBy},
+    sprites::{SpriteSheets, TerrainSprite},
+    weather::Wind,
 };
 
 /// Bob the items up & down
@@ -56,6 +62,123 @@ pub fn drop_item(player: Single<(&WorldPos, &Holding), With<Player>>, mut comman
         .insert(GroundItemBundle::new(player.0));
 }
 
+/// Drop a single item from the held stack on the ground
+pub fn drop_one(
+    player: Single<(&WorldPos, &Holding), With<Player>>,
+    mut held_items: Query<(&ItemType, &mut Quantity), With<HeldBy>>,
+    freshness: Query<&Freshness>,
+    sprite_sheets: Res<SpriteSheets>,
+    mut commands: Commands,
+) {
+    let (player_pos, holding) = *player;
+
+    let held_item = holding
+        .iter()
+        .next()
+        .expect("This system only runs if there is an item being held");
+    let Ok((item_type, mut quantity)) = held_items.get_mut(held_item) else {
+        return;
+    };
+
+    if quantity.0 <= 1 {
+        // Last one, so drop the whole thing
+        commands
+            .entity(held_item)
+            .remove::<HeldItemBundle>()
+            .insert(GroundItemBundle::new(player_pos));
+        return;
+    }
+
+    info!("Dropping one {:?}", item_type);
+    quantity.0 -= 1;
+    let item = item_type.spawn_stack(&mut commands, &sprite_sheets, 1);
+    commands
+        .entity(item)
+        .insert(GroundItemBundle::new(player_pos));
+    if let Ok(freshness) = freshness.get(held_item) {
+        commands.entity(item).insert(*freshness);
+    }
+}
+
+/// Add the targetted ground item to the held stack of the same type, as far as it'll fit
+pub fn pickup_into_stack(
+    ground_item: Single<Entity, (With<GroundItem>, With<TargettedBy>)>,
+    player: Single<&Holding, With<Player>>,
+    mut items: Query<(&ItemType, &mut Quantity)>,
+    mut freshness: Query<&mut Freshness>,
+    mut commands: Commands,
+) {
+    let Some(held_item) = player.iter().next() else {
+        return;
+    };
+    let Ok([(held_type, mut held), (ground_type, mut ground)]) =
+        items.get_many_mut([held_item, *ground_item])
+    else {
+        return;
+    };
+    if held_type != ground_type {
+        // Can only stack the same type of item
+        return;
+    }
+
+    let moved = held.take_from(&mut ground, held_type.max_stack_size());
+    Freshness::merge_stacks(
+        &mut freshness,
+        held_item,
+        *ground_item,
+        held.0 - moved,
+        moved,
+    );
+    info!("Picked up {} {:?} into held stack", moved, held_type);
+    if ground.0 == 0 {
+        commands.entity(*ground_item).despawn();
+    }
+}
+
+/// Merge items on the ground into other stacks of the same type over the same tile
+pub fn merge_ground_stacks(
+    moved: Query<
+        (Entity, &WorldPos),
+        (With<GroundItem>, Or<(Added<GroundItem>, Changed<WorldPos>)>),
+    >,
+    mut items: Query<(&ItemType, &mut Quantity), With<GroundItem>>,
+    mut freshness: Query<&mut Freshness>,
+    index: Res<GroundItemIndex>,
+    mut commands: Commands,
+) {
+    // Stacks which have been emptied into others this tick
+    let mut emptied = HashSet::new();
+
+    for (item, world_pos) in moved {
+        if emptied.contains(&item) {
+            continue;
+        }
+
+        let tile_pos = GroundItemIndex::tile_of(world_pos);
+        for (other, _) in index.at_tile(&tile_pos) {
+            if other == item || emptied.contains(&other) {
+                continue;
+            }
+            let Ok([(item_type, mut quantity), (other_type, mut other_quantity)]) =
+                items.get_many_mut([item, other])
+            else {
+                continue;
+            };
+            if item_type != other_type {
+                continue;
+            }
+
+            let moved = other_quantity.take_from(&mut quantity, item_type.max_stack_size());
+            Freshness::merge_stacks(&mut freshness, other, item, other_quantity.0 - moved, moved);
+            if quantity.0 == 0 {
+                commands.entity(item).despawn();
+                emptied.insert(item);
+                break;
+            }
+        }
+    }
+}
+
 /// Pick up a nearby item from the ground
 pub fn pickup_item(
     ground_item: Single<Entity, (With<GroundItem>, With<TargettedBy>)>,
@@ -71,36 +194,181 @@ pub fn pickup_item(
         .insert(HeldItemBundle::new(player.0));
 }
 
-/// Roll items according to the terrain gradient
+/// Roll items according to the terrain gradient, bouncing them off solid machines. Items which roll
+/// into water either float off with the wind or sink.
 pub fn roll_items(
-    items: Query<(Entity, &ItemType, &mut WorldPos), With<GroundItem>>,
-    gradients: Chunks<&GradientData>,
+    items: Query<(Entity, &ItemType, &mut WorldPos, &mut Velocity), With<GroundItem>>,
+    chunks: Chunks<(&GradientData, &TerrainData)>,
+    machines: Machines<Option<&Ports>, With<Placed>>,
+    wind: Res<Wind>,
     timer: Res<Time>,
     mut commands: Commands,
 ) {
-    for (item, item_type, mut item_pos) in items {
-        let (chunk_pos, offset) = item_pos.tile().to_chunk_offset();
+    let dt = timer.delta_secs();
 
-        let gradients = gradients
-            .get(&chunk_pos)
-            .expect("Chunk should already be generated if there's an item on the ground");
+    // Machines which take items from their own tile (eg. picker-uppers) let items roll onto them
+    let solid = |tile_pos: &TilePos| {
+        machines
+            .get(tile_pos)
+            .is_some_and(|ports| !ports.is_some_and(|ports| ports.inputs.contains(&Face::Centre)))
+    };
 
-        let tile_gradient = gradients.0[offset.y as usize][offset.x as usize];
+    for (item, item_type, mut item_pos, mut velocity) in items {
+        let tile_pos = GroundItemIndex::tile_of(&item_pos);
+        let (chunk_pos, offset) = tile_pos.to_chunk_offset();
 
-        if tile_gradient.length_squared() < ROLL_FRICTION.powi(2) {
-            // Ground isn't steep enough to roll
+        let Some((gradients, terrain)) = chunks.get(&chunk_pos) else {
+            // Off the edge of the generated world
             continue;
+        };
+
+        let properties = item_type.physical_properties();
+
+        let floating = terrain.0[offset.y as usize][offset.x as usize] == TerrainSprite::Water;
+        if floating {
+            if !properties.floats {
+                info!("{:?} sank", item_type);
+                commands.entity(item).despawn();
+                continue;
+            }
+
+            // Drift along with the wind, the water soaks up any other momentum
+            let drift = wind.velocity() * WATER_DRIFT;
+            velocity.0 = velocity.0.lerp(drift, (WATER_DRAG * dt).min(1.));
+        } else {
+            let tile_gradient = gradients.0[offset.y as usize][offset.x as usize];
+            let downhill = -tile_gradient * properties.rollability;
+
+            if velocity.0.length() < ITEM_SETTLE_SPEED && downhill.length() < ROLL_FRICTION {
+                // Resting in a dip, or the ground isn't steep enough to get going
+                velocity.0 = Vec2::ZERO;
+                continue;
+            }
+
+            // Speed up downhill, and slow down from friction without going backwards
+            velocity.0 += downhill * ITEM_ROLL_ACCELERATION * dt;
+            let speed = velocity.0.length();
+            let slowed = (speed - properties.friction * dt).max(0.);
+            velocity.0 = velocity.0.normalize_or_zero() * slowed.min(ITEM_MAX_ROLL_SPEED);
         }
 
-        // Roll the item dowmhill
-        // TODO: rollability, etc.
-        let roll_distance = tile_gradient * timer.delta_secs() * ITEM_ROLL_SPEED;
-        item_pos.0 -= roll_distance;
+        let old_pos = item_pos.0;
+        let mut new_pos = old_pos + velocity.0 * dt;
 
+        // Bounce off machines along whichever axis ran into them. Items that are already over a
+        // machine (eg. just been output onto it) are free to roll off.
+        let blocked = |pos: Vec2| {
+            let tile = GroundItemIndex::tile_of(&WorldPos(pos));
+            tile != tile_pos && solid(&tile)
+        };
+        if !solid(&tile_pos) {
+            if blocked(Vec2::new(new_pos.x, old_pos.y)) {
+                new_pos.x = old_pos.x;
+                velocity.0.x *= -ITEM_RESTITUTION;
+            }
+            if blocked(new_pos) {
+                new_pos.y = old_pos.y;
+                velocity.0.y *= -ITEM_RESTITUTION;
+            }
+        }
+
+        let new_chunk = GroundItemIndex::tile_of(&WorldPos(new_pos))
+            .to_chunk_offset()
+            .0;
+        if chunks.get(&new_chunk).is_none() {
+            // Don't drift or roll out of the generated world
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+
+        let distance = new_pos.distance(old_pos);
+        if distance == 0. {
+            continue;
+        }
+        item_pos.0 = new_pos;
+
+        if floating {
+            // Only rolling counts, otherwise things left floating in a lake rack up distance
+            continue;
+        }
         commands.trigger(ItemRolled {
             entity: item,
             item: *item_type,
-            distance: roll_distance.length(),
+            distance,
         });
     }
 }
+
+/// Knock rolling items into the ones around them, passing momentum between them based on their
+/// mass. Stackable items of the same type merge instead.
+pub fn collide_items(
+    mut items: Query<(Entity, &ItemType, &WorldPos, &mut Velocity), With<GroundItem>>,
+    index: Res<GroundItemIndex>,
+) {
+    let moving = items
+        .iter()
+        .filter(|(.., velocity)| velocity.0 != Vec2::ZERO)
+        .map(|(item, _, world_pos, _)| (item, *world_pos))
+        .collect::<Vec<_>>();
+
+    for (item, world_pos) in moving {
+        for (other, ..) in index.in_radius(&world_pos, ITEM_COLLISION_RADIUS * 2.) {
+            let Ok(
+                [
+                    (_, item_type, item_pos, mut velocity),
+                    (_, other_type, other_pos, mut other_velocity),
+                ],
+            ) = items.get_many_mut([item, other])
+            else {
+                // Also catches the item itself
+                continue;
+            };
+            if item_type == other_type && item_type.max_stack_size() > 1 {
+                // These get merged together instead
+                continue;
+            }
+
+            let normal = (other_pos.0 - item_pos.0).normalize_or_zero();
+            let closing_speed = (velocity.0 - other_velocity.0).dot(normal);
+            if closing_speed <= 0. {
+                // Already moving apart
+                continue;
+            }
+
+            let (mass, other_mass) = (
+                item_type.physical_properties().mass,
+                other_type.physical_properties().mass,
+            );
+            let impulse = (1. + ITEM_RESTITUTION) * closing_speed / (1. / mass + 1. / other_mass);
+            velocity.0 -= normal * impulse / mass;
+            other_velocity.0 += normal * impulse / other_mass;
+        }
+    }
+}
+
+/// Add items to the spatial index as they land on the ground
+pub fn index_ground_item(
+    event: On<Add, GroundItem>,
+    items: Query<&WorldPos>,
+    mut index: ResMut<GroundItemIndex>,
+) {
+    let world_pos = items
+        .get(event.entity)
+        .expect("Ground items are always given a position");
+    index.insert(event.entity, *world_pos);
+}
+
+/// Remove items from the spatial index as they're picked up or despawned
+pub fn unindex_ground_item(event: On<Remove, GroundItem>, mut index: ResMut<GroundItemIndex>) {
+    index.remove(event.entity);
+}
+
+/// Keep the spatial index up to date as items move around on the ground
+pub fn update_ground_item_index(
+    items: Query<(Entity, &WorldPos), (With<GroundItem>, Changed<WorldPos>)>,
+    mut index: ResMut<GroundItemIndex>,
+) {
+    for (item, world_pos) in items {
+        index.insert(item, *world_pos);
+    }
+}
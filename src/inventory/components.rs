@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::{
+    consts::{INVENTORY_SIZE, Z_HELD_ITEM},
+    player::{HeldItemBundle, Holding},
+};
+
+/// The player's inventory. The item in the selected slot is the one being held, and the items in
+/// the other slots are stowed away.
+#[derive(Component, Default, Debug)]
+pub struct Inventory {
+    pub selected: usize,
+}
+impl Inventory {
+    /// The item in each slot, if there is one
+    pub fn slots(
+        &self,
+        holding: Option<&Holding>,
+        stowing: Option<&Stowing>,
+        stowed_items: &Query<&InventorySlot>,
+    ) -> [Option<Entity>; INVENTORY_SIZE] {
+        let mut slots = [None; INVENTORY_SIZE];
+        slots[self.selected] = holding.and_then(|holding| holding.iter().next());
+        for item in stowing.into_iter().flat_map(|stowing| stowing.iter()) {
+            let slot = stowed_items
+                .get(item)
+                .expect("Stowed items are always given a slot");
+            slots[slot.0] = Some(item);
+        }
+
+        slots
+    }
+
+    /// Put an item into a slot, straight into the holder's hands if it's the selected one
+    pub fn put_in_slot(&self, item: &mut EntityCommands, holder: Entity, slot: usize) {
+        if slot == self.selected {
+            item.insert(HeldItemBundle::new(holder));
+        } else {
+            item.insert(StowedItemBundle::new(holder, slot));
+        }
+    }
+
+    /// First empty slot, preferring the selected one
+    pub fn free_slot(&self, slots: &[Option<Entity>; INVENTORY_SIZE]) -> Option<usize> {
+        if slots[self.selected].is_none() {
+            return Some(self.selected);
+        }
+
+        slots.iter().position(Option::is_none)
+    }
+}
+
+/// Relationship for an item which is in one of the inventory slots that isn't selected
+#[derive(Component)]
+#[relationship(relationship_target = Stowing)]
+pub struct StowedBy(pub Entity);
+
+/// Relationship for when the entity has one or more things stowed away in its inventory
+#[derive(Component, Debug)]
+#[relationship_target(relationship = StowedBy)]
+pub struct Stowing(Vec<Entity>);
+
+/// The inventory slot a stowed item is in
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InventorySlot(pub usize);
+
+/// Bundle of components for an item stowed away in the inventory
+#[derive(Bundle)]
+pub struct StowedItemBundle {
+    parent: ChildOf,
+    stowed: StowedBy,
+    slot: InventorySlot,
+    transform: Transform,
+    visibility: Visibility,
+}
+impl StowedItemBundle {
+    pub fn new(holder: Entity, slot: usize) -> Self {
+        Self {
+            parent: ChildOf(holder),
+            stowed: StowedBy(holder),
+            slot: InventorySlot(slot),
+            // Same place as held items, but hidden until the slot is selected
+            transform: Transform::from_xyz(0.5, 0.5, Z_HELD_ITEM),
+            visibility: Visibility::Hidden,
+        }
+    }
+}
+
+/// Message selecting which inventory slot is held
+#[derive(Message)]
+pub struct SelectSlot(pub usize);
+
+/// Message moving items between the inventory and the held container
+#[derive(Message, Debug)]
+pub enum ContainerTransfer {
+    /// Put the stack in this inventory slot into the container
+    In(usize),
+    /// Take this item out of the held or targetted container and put it in the inventory
+    Out(Entity),
+}
+
+/// Top-level marker for the hotbar UI
+#[derive(Component)]
+pub struct Hotbar;
+
+/// Hotbar button for an inventory slot
+#[derive(Component)]
+pub struct HotbarSlot(pub usize);
+
+/// Text describing the stack in an inventory slot
+#[derive(Component)]
+pub struct InventorySlotText(pub usize);
+
+/// Resource controlling whether the inventory window is shown
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct InventoryOpen(pub bool);
+
+/// Top-level marker for the inventory window
+#[derive(Component)]
+pub struct InventoryWindow;
+
+/// Inventory window button which puts the stack in a slot into the held container
+#[derive(Component)]
+pub struct StowedItemButton(pub usize);
+
+/// Inventory window section which the container view is shown in while the window is open
+#[derive(Component)]
+pub struct ContainerPanel;
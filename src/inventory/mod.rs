@@ -0,0 +1,34 @@
+mod components;
+mod systems;
+
+use bevy::prelude::*;
+pub use components::*;
+use systems::*;
+
+use crate::utils::run_if::key_just_pressed;
+
+pub struct InventoryPlugin;
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InventoryOpen>()
+            .add_message::<SelectSlot>()
+            .add_message::<ContainerTransfer>()
+            .add_systems(Startup, setup_hotbar)
+            .add_systems(
+                Update,
+                (
+                    (select_slot_keys, hotbar_button, switch_slot).chain(),
+                    (
+                        toggle_inventory.run_if(key_just_pressed(KeyCode::Tab)),
+                        (show_inventory, stowed_item_button)
+                            .chain()
+                            .run_if(resource_equals(InventoryOpen(true))),
+                        hide_inventory.run_if(resource_equals(InventoryOpen(false))),
+                    )
+                        .chain(),
+                    transfer_container_items,
+                    update_slot_texts,
+                ),
+            );
+    }
+}
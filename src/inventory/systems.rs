@@ -0,0 +1,381 @@
+use bevy::prelude::*;
+
+use super::components::*;
+use crate::{
+    consts::INVENTORY_SIZE,
+    container::{
+        ContainableItems, ContainedBundle, ContainedBy, Container, ContainerCapacity, Contains,
+    },
+    items::{Freshness, ItemType, Quantity},
+    player::{HeldBy, HeldItemBundle, Holding, Player, TargettedBy},
+    tools::Durability,
+};
+
+/// Keys used to select each inventory slot
+const SLOT_KEYS: [KeyCode; INVENTORY_SIZE] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Spawn the hotbar along the bottom of the screen
+pub fn setup_hotbar(mut commands: Commands) {
+    commands.spawn((
+        Hotbar,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(10),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        Children::spawn(SpawnIter((0..INVENTORY_SIZE).map(|slot| {
+            (
+                Button,
+                HotbarSlot(slot),
+                Node {
+                    width: px(72),
+                    border: UiRect::all(px(2)),
+                    margin: UiRect::all(px(2)),
+                    padding: UiRect::all(px(4)),
+                    ..Default::default()
+                },
+                BorderColor::all(Color::WHITE),
+                BackgroundColor(Color::BLACK),
+                children![(
+                    Text::default(),
+                    TextFont {
+                        font_size: 12.,
+                        ..Default::default()
+                    },
+                    InventorySlotText(slot),
+                )],
+            )
+        }))),
+    ));
+}
+
+/// Select a slot with the number keys
+pub fn select_slot_keys(inputs: Res<ButtonInput<KeyCode>>, mut writer: MessageWriter<SelectSlot>) {
+    for (slot, key) in SLOT_KEYS.iter().enumerate() {
+        if inputs.just_pressed(*key) {
+            writer.write(SelectSlot(slot));
+        }
+    }
+}
+
+/// Interaction with hotbar slots, clicking one selects it
+pub fn hotbar_button(
+    buttons: Query<(&Interaction, &mut BackgroundColor, &HotbarSlot), Changed<Interaction>>,
+    mut writer: MessageWriter<SelectSlot>,
+) {
+    for (interaction, mut bg_color, slot) in buttons {
+        match interaction {
+            Interaction::Pressed => {
+                writer.write(SelectSlot(slot.0));
+            }
+            Interaction::Hovered => *bg_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            Interaction::None => *bg_color = BackgroundColor(Color::BLACK),
+        }
+    }
+}
+
+/// Stow away the held item and take out the one in the newly selected slot
+pub fn switch_slot(
+    mut reader: MessageReader<SelectSlot>,
+    player: Single<(Entity, &mut Inventory, Option<&Holding>, Option<&Stowing>), With<Player>>,
+    stowed_items: Query<&InventorySlot>,
+    mut commands: Commands,
+) {
+    let Some(SelectSlot(slot)) = reader.read().last() else {
+        return;
+    };
+    let (player, mut inventory, holding, stowing) = player.into_inner();
+    if *slot == inventory.selected {
+        return;
+    }
+
+    info!("Selecting inventory slot {}", slot);
+
+    let slots = inventory.slots(holding, stowing, &stowed_items);
+    if let Some(held) = slots[inventory.selected] {
+        commands
+            .entity(held)
+            .remove::<HeldItemBundle>()
+            .insert(StowedItemBundle::new(player, inventory.selected));
+    }
+    if let Some(stowed) = slots[*slot] {
+        commands
+            .entity(stowed)
+            .remove::<StowedItemBundle>()
+            .insert((HeldItemBundle::new(player), Visibility::Inherited));
+    }
+
+    inventory.selected = *slot;
+}
+
+/// Keep the slot descriptions & the selected slot highlight up to date
+pub fn update_slot_texts(
+    player: Single<(&Inventory, Option<&Holding>, Option<&Stowing>), With<Player>>,
+    stowed_items: Query<&InventorySlot>,
+    items: Query<(&ItemType, &Quantity, Option<&Durability>)>,
+    texts: Query<(&mut Text, &InventorySlotText)>,
+    buttons: Query<(&mut BorderColor, &HotbarSlot)>,
+) {
+    let (inventory, holding, stowing) = *player;
+    let slots = inventory.slots(holding, stowing, &stowed_items);
+
+    for (mut text, slot) in texts {
+        let stack = slots[slot.0].and_then(|item| items.get(item).ok()).map_or(
+            "-".to_string(),
+            |(item_type, quantity, durability)| {
+                let name = item_type.stack_name(quantity.0);
+                match durability {
+                    // Show how worn tools are
+                    Some(durability) => {
+                        format!("{} ({}/{})", name, durability.remaining, durability.max)
+                    }
+                    None => name,
+                }
+            },
+        );
+        text.0 = format!("{}: {}", slot.0 + 1, stack);
+    }
+
+    for (mut border, slot) in buttons {
+        *border = BorderColor::all(if slot.0 == inventory.selected {
+            Color::srgb(1., 1., 0.)
+        } else {
+            Color::WHITE
+        });
+    }
+}
+
+/// Toggle whether the inventory window is shown.
+pub fn toggle_inventory(mut open: ResMut<InventoryOpen>) {
+    open.0 ^= true;
+    if open.0 {
+        info!("Opening inventory");
+    } else {
+        info!("Closing inventory");
+    }
+}
+
+/// Spawn the inventory window
+pub fn show_inventory(window: Option<Single<(), With<InventoryWindow>>>, mut commands: Commands) {
+    if window.is_some() {
+        return;
+    }
+
+    commands.spawn((
+        InventoryWindow,
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(10),
+            width: percent(100),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        // Inventory slots on the left, the contents of the container on the right
+        children![(
+            Node {
+                flex_direction: FlexDirection::Row,
+                border: UiRect::all(px(2)),
+                padding: UiRect::all(px(4)),
+                ..Default::default()
+            },
+            BorderColor::all(Color::WHITE),
+            BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+            children![
+                (
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        margin: UiRect::right(px(8)),
+                        ..Default::default()
+                    },
+                    Children::spawn((
+                        Spawn((
+                            Text::new("Inventory (click to put in held container)"),
+                            Node {
+                                margin: UiRect::all(px(2)),
+                                ..Default::default()
+                            },
+                        )),
+                        SpawnIter((0..INVENTORY_SIZE).map(|slot| {
+                            (
+                                Button,
+                                StowedItemButton(slot),
+                                Node {
+                                    border: UiRect::all(px(2)),
+                                    margin: UiRect::all(px(2)),
+                                    padding: UiRect::all(px(4)),
+                                    ..Default::default()
+                                },
+                                BorderColor::all(Color::WHITE),
+                                BackgroundColor(Color::BLACK),
+                                children![(
+                                    Text::default(),
+                                    TextFont {
+                                        font_size: 14.,
+                                        ..Default::default()
+                                    },
+                                    InventorySlotText(slot),
+                                )],
+                            )
+                        })),
+                    )),
+                ),
+                (
+                    ContainerPanel,
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        min_width: px(120),
+                        ..Default::default()
+                    },
+                ),
+            ],
+        )],
+    ));
+}
+
+/// Despawn the inventory window.
+pub fn hide_inventory(
+    window: Option<Single<Entity, With<InventoryWindow>>>,
+    mut commands: Commands,
+) {
+    if let Some(window) = window {
+        commands.entity(*window).despawn();
+    }
+}
+
+/// Interaction with inventory slots in the window, clicking one puts it in the held container
+pub fn stowed_item_button(
+    buttons: Query<(&Interaction, &mut BackgroundColor, &StowedItemButton), Changed<Interaction>>,
+    mut writer: MessageWriter<ContainerTransfer>,
+) {
+    for (interaction, mut bg_color, slot) in buttons {
+        match interaction {
+            Interaction::Pressed => {
+                writer.write(ContainerTransfer::In(slot.0));
+            }
+            Interaction::Hovered => *bg_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            Interaction::None => *bg_color = BackgroundColor(Color::BLACK),
+        }
+    }
+}
+
+/// Move stacks between the inventory and containers, topping up existing stacks of the same item
+/// first. Items can go into the held container, and be taken out of either the held or targetted
+/// one.
+pub fn transfer_container_items(
+    mut reader: MessageReader<ContainerTransfer>,
+    player: Single<(Entity, &Inventory, Option<&Holding>, Option<&Stowing>), With<Player>>,
+    containers: Query<
+        (
+            Entity,
+            &ContainableItems,
+            &ContainerCapacity,
+            Option<&Contains>,
+            Has<HeldBy>,
+        ),
+        (With<Container>, Or<(With<HeldBy>, With<TargettedBy>)>),
+    >,
+    stowed_items: Query<&InventorySlot>,
+    contained_items: Query<&ContainedBy>,
+    mut items: Query<(&ItemType, &mut Quantity)>,
+    mut freshness: Query<&mut Freshness>,
+    mut commands: Commands,
+) {
+    let (player, inventory, holding, stowing) = *player;
+
+    let mut slots = inventory.slots(holding, stowing, &stowed_items);
+    for transfer in reader.read() {
+        info!("Container transfer: {:?}", transfer);
+
+        match *transfer {
+            ContainerTransfer::In(slot) => {
+                let Some((container, containables, capacity, contains, _)) =
+                    containers.iter().find(|(.., held)| *held)
+                else {
+                    // Nothing to put it in
+                    continue;
+                };
+                // The selected slot is the container itself
+                let Some(item) = slots[slot].filter(|_| slot != inventory.selected) else {
+                    continue;
+                };
+                let item_type = *items.get(item).expect("Stowed items have a type").0;
+                if !containables.0.contains(&item_type) {
+                    // Item can't be put in this container
+                    continue;
+                }
+
+                if contains
+                    .is_some_and(|contains| contains.top_up(item, &mut items, &mut freshness))
+                {
+                    commands.entity(item).despawn();
+                    slots[slot] = None;
+                    continue;
+                }
+
+                if !capacity.has_room(contains) {
+                    info!("Container is full");
+                    continue;
+                }
+
+                commands
+                    .entity(item)
+                    .remove::<StowedItemBundle>()
+                    .insert((ContainedBundle::new(container), Visibility::Inherited));
+                slots[slot] = None;
+            }
+            ContainerTransfer::Out(item) => {
+                if !contained_items
+                    .get(item)
+                    .is_ok_and(|contained_by| containers.contains(contained_by.0))
+                {
+                    // Not in a container the player can reach
+                    continue;
+                }
+                let item_type = *items.get(item).expect("Contained items have a type").0;
+
+                // Top up stacks in the inventory
+                for stack in slots.iter().flatten() {
+                    if let Ok([(stack_type, mut stack_quantity), (_, mut quantity)]) =
+                        items.get_many_mut([*stack, item])
+                        && *stack_type == item_type
+                    {
+                        let moved =
+                            stack_quantity.take_from(&mut quantity, item_type.max_stack_size());
+                        Freshness::merge_stacks(
+                            &mut freshness,
+                            *stack,
+                            item,
+                            stack_quantity.0 - moved,
+                            moved,
+                        );
+                    }
+                }
+                if items.get(item).is_ok_and(|(_, quantity)| quantity.0 == 0) {
+                    commands.entity(item).despawn();
+                    continue;
+                }
+
+                let Some(slot) = inventory.free_slot(&slots) else {
+                    info!("Inventory is full");
+                    continue;
+                };
+                let mut item_commands = commands.entity(item);
+                item_commands.remove::<ContainedBundle>();
+                inventory.put_in_slot(&mut item_commands, player, slot);
+                slots[slot] = Some(item);
+            }
+        }
+    }
+}
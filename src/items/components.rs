@@ -1,16 +1,24 @@
-use bevy::{platform::collections::HashSet, prelude::*};
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use num_enum::TryFromPrimitive;
 
 use crate::{
-    container::{ContainableItems, Container},
+    container::ContainerBundle,
     machines::{
-        HarvesterBundle, PickerUpperBundle, TransporterBundle, WaterWheelBundle, WindmillBundle,
+        BatteryBundle, BurnerBundle, Footprint, HarvestArea, HarvesterBundle, PickerUpperBundle,
+        PickupRadius, PowerPoleBundle, TransporterBundle, UndergroundBeltBundle, UndergroundEnd,
+        WaterMillBundle, WaterWheelBundle, WindmillBundle,
     },
     resources::{ResourceNodeType, ResourceType},
     sprites::{EntitySprite, GetSprite, ItemSprite, SpriteSheets, TerrainSprite},
+    tools::ToolBundle,
 };
 
 /// Items that can be held / moved around
-#[derive(Component, Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Component, Clone, Copy, Hash, PartialEq, Eq, Debug, TryFromPrimitive)]
+#[repr(u8)]
+#[require(Quantity)]
 pub enum ItemType {
     Berry,
     Log,
@@ -22,6 +30,20 @@ pub enum ItemType {
     TripAxe,
     WaterWheel,
     Windmill,
+    Battery,
+    PowerPole,
+    WaterMill,
+    Burner,
+    UndergroundEntrance,
+    UndergroundExit,
+    Feller,
+    Collector,
+    Bucket,
+    Basket,
+    Crate,
+    Rotten,
+    Axe,
+    Sickle,
 }
 
 impl ItemType {
@@ -38,6 +60,20 @@ impl ItemType {
             TripAxe => ItemSprite::TripAxe,
             WaterWheel => ItemSprite::WaterWheel,
             Windmill => ItemSprite::Windmill,
+            Battery => ItemSprite::Battery,
+            PowerPole => ItemSprite::PowerPole,
+            WaterMill => ItemSprite::WaterMill,
+            Burner => ItemSprite::Burner,
+            UndergroundEntrance => ItemSprite::UndergroundEntrance,
+            UndergroundExit => ItemSprite::UndergroundExit,
+            Feller => ItemSprite::Feller,
+            Collector => ItemSprite::Collector,
+            Bucket => ItemSprite::Bucket,
+            Basket => ItemSprite::Basket,
+            Crate => ItemSprite::Crate,
+            Rotten => ItemSprite::Rotten,
+            Axe => ItemSprite::Axe,
+            Sickle => ItemSprite::Sickle,
         }
     }
 
@@ -55,27 +91,151 @@ impl ItemType {
             TripAxe => None,
             WaterWheel => None,
             Windmill => None,
+            Battery => None,
+            PowerPole => None,
+            WaterMill => None,
+            Burner => None,
+            UndergroundEntrance => None,
+            UndergroundExit => None,
+            Feller => None,
+            Collector => None,
+            Bucket => None,
+            Basket => None,
+            Crate => None,
+            // Spoiled food isn't worth anything
+            Rotten => None,
+            Axe => None,
+            Sickle => None,
+        }
+    }
+
+    /// Most of this item that can be in one stack
+    pub fn max_stack_size(&self) -> usize {
+        use ItemType::*;
+        match self {
+            Berry => 20,
+            Log => 10,
+            Water => 5,
+            Rotten => 20,
+            // Items with their own state (machines, containers) don't stack
+            _ => 1,
+        }
+    }
+
+    /// Describe a stack of this item
+    pub fn stack_name(&self, quantity: usize) -> String {
+        if quantity > 1 {
+            format!("{:?} x{}", self, quantity)
+        } else {
+            format!("{:?}", self)
+        }
+    }
+
+    /// Seconds this item lasts before it spoils, if it's perishable
+    pub fn shelf_life(&self) -> Option<f32> {
+        use ItemType::*;
+        match self {
+            Berry => Some(120.),
+            Water => Some(300.),
+            _ => None,
+        }
+    }
+
+    /// How this item moves around when it's loose on the ground
+    pub fn physical_properties(&self) -> PhysicalProperties {
+        use ItemType::*;
+        let (rollability, mass, friction, floats) = match self {
+            Berry => (1., 0.2, 0.5, true),
+            Log => (0.6, 3., 1., true),
+            Water => (1., 1., 0.2, false),
+            Rotten => (0.3, 0.2, 2., true),
+            Bowl | Bucket | Basket | Crate => (0.3, 1., 2., true),
+            Axe | Sickle => (0.1, 1.5, 3., false),
+            // Machines are big & clunky
+            _ => (0.1, 5., 4., false),
+        };
+        PhysicalProperties {
+            rollability,
+            mass,
+            friction,
+            floats,
+        }
+    }
+
+    /// Spawn a stack of this item in the void, with its sprite
+    pub fn spawn_stack(
+        &self,
+        commands: &mut Commands,
+        sprite_sheets: &SpriteSheets,
+        quantity: usize,
+    ) -> Entity {
+        let mut entity_commands = commands.spawn((*self, Quantity(quantity)));
+        self.add_extra_components(&mut entity_commands);
+        let entity = entity_commands.id();
+
+        self.spawn_sprite(commands, sprite_sheets, Some(entity));
+        entity
+    }
+
+    /// Items that can be burnt as fuel
+    pub const FUELS: [Self; 1] = [ItemType::Log];
+
+    /// Seconds this item keeps a burner going for, if it's a fuel
+    pub fn burn_time(&self) -> Option<f32> {
+        use ItemType::*;
+        match self {
+            Log => Some(10.),
+            _ => None,
+        }
+    }
+
+    /// Tiles covered by this item when it's placed down as a machine
+    pub fn footprint(&self) -> Footprint {
+        use ItemType::*;
+        match self {
+            // A long bank of cells
+            Battery => Footprint(UVec2::new(2, 1)),
+            _ => Footprint::default(),
         }
     }
 
     /// Adds extra item-specific components to an entity
     pub fn add_extra_components(&self, commands: &mut EntityCommands) {
+        if self.shelf_life().is_some() {
+            commands.insert(Freshness::default());
+        }
+        if self.footprint() != Footprint::default() {
+            commands.insert(self.footprint());
+        }
+
         use ItemType::*;
         match self {
             Bowl => {
+                commands.insert(ContainerBundle::new(1, 1., [Water]));
+            }
+            Bucket => {
                 commands.insert((
-                    Container,
-                    ContainableItems({
-                        let mut set = HashSet::new();
-                        set.insert(Water);
-                        set
-                    }),
+                    ContainerBundle::new(3, 0.75, [Water]),
+                    ToolBundle::new([ResourceNodeType::Water], 2, 100),
                 ));
             }
+            Axe => {
+                commands.insert(ToolBundle::new([ResourceNodeType::Tree], 2, 50));
+            }
+            Sickle => {
+                commands.insert(ToolBundle::new([ResourceNodeType::Bush], 3, 50));
+            }
+            Basket => {
+                commands.insert(ContainerBundle::new(4, 0.5, [Berry]));
+            }
+            Crate => {
+                commands.insert(ContainerBundle::new(6, 0.25, [Berry, Log]));
+            }
             BushWhacker => {
                 commands.insert(HarvesterBundle::new(
                     2.,
                     1.,
+                    0.1,
                     [ResourceNodeType::Bush],
                     vec![EntitySprite::BushWhacker1, EntitySprite::BushWhacker2],
                 ));
@@ -83,7 +243,9 @@ impl ItemType {
             Transporter => {
                 commands.insert(TransporterBundle::new(
                     2.,
-                    1.,
+                    0.5,
+                    0.1,
+                    0.25,
                     vec![EntitySprite::Transporter],
                 ));
             }
@@ -91,21 +253,44 @@ impl ItemType {
                 commands.insert(PickerUpperBundle::new(
                     2.,
                     1.,
+                    0.1,
                     vec![EntitySprite::PickerUpper],
                 ));
             }
+            Collector => {
+                commands.insert((
+                    PickerUpperBundle::new(1., 1.5, 0.2, vec![EntitySprite::Collector]),
+                    // Sweeps up anything that rolls nearby
+                    PickupRadius(3.),
+                ));
+            }
             TripAxe => {
                 commands.insert(HarvesterBundle::new(
                     2.,
                     1.,
+                    0.1,
                     [ResourceNodeType::Tree],
                     vec![EntitySprite::TripAxe1, EntitySprite::TripAxe2],
                 ));
             }
+            Feller => {
+                commands.insert((
+                    HarvesterBundle::new(
+                        1.,
+                        2.,
+                        0.2,
+                        [ResourceNodeType::Tree],
+                        vec![EntitySprite::Feller1, EntitySprite::Feller2],
+                    ),
+                    // Works the 3x3 patch of trees in front of it
+                    HarvestArea(UVec2::splat(3)),
+                ));
+            }
             WaterWheel => {
                 commands.insert(WaterWheelBundle::new(
                     2.,
                     1.,
+                    0.1,
                     [TerrainSprite::Water],
                     vec![EntitySprite::WaterWheel1, EntitySprite::WaterWheel2],
                 ));
@@ -116,13 +301,136 @@ impl ItemType {
                     vec![EntitySprite::Windmill1, EntitySprite::Windmill2],
                 ));
             }
+            Battery => {
+                commands.insert(BatteryBundle::new(20., 2., vec![EntitySprite::Battery]));
+            }
+            PowerPole => {
+                commands.insert(PowerPoleBundle::new(6., vec![EntitySprite::PowerPole]));
+            }
+            WaterMill => {
+                commands.insert(WaterMillBundle::new(
+                    20.,
+                    2.,
+                    vec![EntitySprite::WaterMill1, EntitySprite::WaterMill2],
+                ));
+            }
+            Burner => {
+                commands.insert(BurnerBundle::new(
+                    3.,
+                    60.,
+                    2.,
+                    vec![EntitySprite::Burner1, EntitySprite::Burner2],
+                ));
+            }
+            UndergroundEntrance => {
+                commands.insert(UndergroundBeltBundle::new(
+                    UndergroundEnd::Entrance,
+                    4,
+                    2.,
+                    0.5,
+                    0.1,
+                    0.25,
+                    vec![EntitySprite::UndergroundEntrance],
+                ));
+            }
+            UndergroundExit => {
+                commands.insert(UndergroundBeltBundle::new(
+                    UndergroundEnd::Exit,
+                    4,
+                    2.,
+                    0.5,
+                    0.1,
+                    0.25,
+                    vec![EntitySprite::UndergroundExit],
+                ));
+            }
             _ => (),
         }
     }
 }
 
+/// Parse an item type from its name, as written by `Debug`
+impl FromStr for ItemType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        (0..std::mem::variant_count::<Self>() as u8)
+            .map(|i| Self::try_from(i).expect("Item types are contiguous"))
+            .find(|item_type| format!("{item_type:?}") == s)
+            .ok_or_else(|| s.to_string())
+    }
+}
+
 impl GetSprite for ItemType {
     fn get_sprite(&self, sprite_sheets: &SpriteSheets) -> Sprite {
         self.sprite_type().get_sprite(sprite_sheets)
     }
 }
+
+/// Number of items in a stack
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Quantity(pub usize);
+impl Default for Quantity {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+impl Quantity {
+    /// Move as many items from `other` into this stack as will fit, returning how many moved
+    pub fn take_from(&mut self, other: &mut Self, max_stack_size: usize) -> usize {
+        let moved = other.0.min(max_stack_size.saturating_sub(self.0));
+        self.0 += moved;
+        other.0 -= moved;
+
+        moved
+    }
+}
+
+/// How an item behaves when it's loose on the ground
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalProperties {
+    /// How much of the slope is turned into speed, 0 (slides like a brick) to 1 (perfectly round)
+    pub rollability: f32,
+    /// Heavier items knock lighter ones out of the way
+    pub mass: f32,
+    /// How quickly a rolling item slows down, tiles per second^2
+    pub friction: f32,
+    /// Whether the item floats on water, otherwise it sinks
+    pub floats: bool,
+}
+
+/// How fresh a stack of perishable items is, from 1 (just harvested) to 0 (spoiled)
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Freshness(pub f32);
+impl Default for Freshness {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+impl Freshness {
+    /// Blend in `added` items of `other` freshness on top of the `existing` items in this stack
+    pub fn blend(&mut self, existing: usize, added: usize, other: f32) {
+        let total = existing + added;
+        if total > 0 {
+            self.0 = (self.0 * existing as f32 + other * added as f32) / total as f32;
+        }
+    }
+
+    /// Blend the freshness of `added` items moved from the stack `from` into the stack `into`,
+    /// which had `existing` items in it beforehand
+    pub fn merge_stacks(
+        freshness: &mut Query<&mut Freshness>,
+        into: Entity,
+        from: Entity,
+        existing: usize,
+        added: usize,
+    ) {
+        if let Ok([mut into, from]) = freshness.get_many_mut([into, from]) {
+            into.blend(existing, added, from.0);
+        }
+    }
+}
+
+/// Text showing how many items are in a stack
+#[derive(Component)]
+pub struct StackLabel;
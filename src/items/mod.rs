@@ -1,3 +1,16 @@
 mod components;
 mod systems;
+
+use bevy::prelude::*;
 pub use components::*;
+use systems::*;
+
+pub struct ItemPlugin;
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_stack_labels, (spoil_items, rot_items).chain()),
+        );
+    }
+}
@@ -1 +1,89 @@
+use bevy::prelude::*;
 
+use super::components::*;
+use crate::{
+    consts::{STACK_LABEL_FONT_RENDER_SIZE, Z_STACK_LABEL},
+    container::{ContainedBy, SpoilageRate},
+    sprites::{GetSprite, SpriteSheets},
+};
+
+/// Show the size of stacks with more than one item in them
+pub fn update_stack_labels(
+    stacks: Query<(Entity, &Quantity, Option<&Children>), Changed<Quantity>>,
+    labels: Query<(), With<StackLabel>>,
+    mut commands: Commands,
+) {
+    for (item, quantity, children) in stacks {
+        // Clear the old label
+        for label in children
+            .into_iter()
+            .flatten()
+            .filter(|child| labels.contains(**child))
+        {
+            commands.entity(*label).despawn();
+        }
+
+        if quantity.0 <= 1 {
+            // Single items don't need a label
+            continue;
+        }
+
+        // Small number in the bottom-right corner of the item
+        commands.spawn((
+            StackLabel,
+            ChildOf(item),
+            Text2d::new(quantity.0.to_string()),
+            TextFont {
+                font_size: STACK_LABEL_FONT_RENDER_SIZE,
+                ..Default::default()
+            },
+            Transform::from_xyz(0.3, -0.3, Z_STACK_LABEL)
+                .with_scale(Vec2::splat(1. / (STACK_LABEL_FONT_RENDER_SIZE * 2.)).extend(1.)),
+        ));
+    }
+}
+
+/// Perishable items go off over time, more slowly when they're kept in a container
+pub fn spoil_items(
+    items: Query<(&ItemType, &mut Freshness, Option<&ContainedBy>)>,
+    containers: Query<&SpoilageRate>,
+    timer: Res<Time>,
+) {
+    for (item_type, mut freshness, contained_by) in items {
+        let Some(shelf_life) = item_type.shelf_life() else {
+            continue;
+        };
+
+        let rate = contained_by
+            .and_then(|contained_by| containers.get(contained_by.0).ok())
+            .map_or(1., |rate| rate.0);
+        freshness.0 -= timer.delta_secs() * rate / shelf_life;
+    }
+}
+
+/// Turn spoiled items into rotten ones
+pub fn rot_items(
+    items: Query<(Entity, &Freshness, &Children), Changed<Freshness>>,
+    mut sprites: Query<&mut Sprite>,
+    sprite_sheets: Res<SpriteSheets>,
+    mut commands: Commands,
+) {
+    for (item, freshness, children) in items {
+        if freshness.0 > 0. {
+            continue;
+        }
+
+        info!("Item {:?} has rotted", item);
+        commands
+            .entity(item)
+            .remove::<Freshness>()
+            .insert(ItemType::Rotten);
+
+        // Swap the sprite over
+        for child in children {
+            if let Ok(mut sprite) = sprites.get_mut(*child) {
+                *sprite = ItemType::Rotten.get_sprite(&sprite_sheets);
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    crafting::Recipe, items::ItemType, knowledge::UnlockRequirement, resources::ResourceType,
+    crafting::Recipe,
+    items::ItemType,
+    knowledge::UnlockRequirement,
+    resources::{ResourceNodeType, ResourceType},
 };
 
 /// A single knowledge definition .
@@ -50,6 +53,52 @@ pub fn load_knowledge() -> Vec<KnowledgeDef> {
                 reqs: vec![(ResourceType::Wood, 5)],
                 product: ItemType::Bowl,
             }),
+        KnowledgeDef::new("Bucket")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Water,
+                amount: 20,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 10)],
+                product: ItemType::Bucket,
+            }),
+        KnowledgeDef::new("Axe")
+            // Trees can't be harvested by hand, so this is available from the start
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 5)],
+                product: ItemType::Axe,
+            }),
+        KnowledgeDef::new("Sickle")
+            .requirement(UnlockRequirement::TotalGathered {
+                resource: ResourceNodeType::Bush,
+                amount: 10,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 5)],
+                product: ItemType::Sickle,
+            }),
+        KnowledgeDef::new("Basket")
+            .requirement(UnlockRequirement::TotalGathered {
+                resource: ResourceNodeType::Bush,
+                amount: 20,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 10)],
+                product: ItemType::Basket,
+            }),
+        KnowledgeDef::new("Crate")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Wood,
+                amount: 50,
+            })
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Food,
+                amount: 50,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 20)],
+                product: ItemType::Crate,
+            }),
         KnowledgeDef::new("Harvester")
             .requirement(UnlockRequirement::TotalDeposited {
                 resource: ResourceType::Wood,
@@ -78,6 +127,15 @@ pub fn load_knowledge() -> Vec<KnowledgeDef> {
                 reqs: vec![(ResourceType::Wood, 5)],
                 product: ItemType::PickerUpper,
             }),
+        KnowledgeDef::new("Collector")
+            .requirement(UnlockRequirement::TotalRolled {
+                item: ItemType::Log,
+                distance: 100.0,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 20)],
+                product: ItemType::Collector,
+            }),
         KnowledgeDef::new("Trip Axe")
             .requirement(UnlockRequirement::TotalDeposited {
                 resource: ResourceType::Wood,
@@ -91,6 +149,15 @@ pub fn load_knowledge() -> Vec<KnowledgeDef> {
                 reqs: vec![(ResourceType::Wood, 5)],
                 product: ItemType::TripAxe,
             }),
+        KnowledgeDef::new("Feller")
+            .requirement(UnlockRequirement::TotalGathered {
+                resource: ResourceNodeType::Tree,
+                amount: 30,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 25)],
+                product: ItemType::Feller,
+            }),
         KnowledgeDef::new("Water Wheel")
             .requirement(UnlockRequirement::TotalDeposited {
                 resource: ResourceType::Wood,
@@ -113,6 +180,78 @@ pub fn load_knowledge() -> Vec<KnowledgeDef> {
                 reqs: vec![(ResourceType::Wood, 1)],
                 product: ItemType::Windmill,
             }),
+        KnowledgeDef::new("Battery")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Wood,
+                amount: 5,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 10)],
+                product: ItemType::Battery,
+            }),
+        KnowledgeDef::new("Power Pole")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Wood,
+                amount: 5,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 2)],
+                product: ItemType::PowerPole,
+            }),
+        KnowledgeDef::new("Water Mill")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Water,
+                amount: 5,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 5)],
+                product: ItemType::WaterMill,
+            }),
+        KnowledgeDef::new("Burner")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Wood,
+                amount: 10,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 5)],
+                product: ItemType::Burner,
+            }),
+        KnowledgeDef::new("Underground Entrance")
+            .requirement(UnlockRequirement::TotalRolled {
+                item: ItemType::Log,
+                distance: 50.0,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 10)],
+                product: ItemType::UndergroundEntrance,
+            }),
+        KnowledgeDef::new("Underground Exit")
+            .requirement(UnlockRequirement::TotalRolled {
+                item: ItemType::Log,
+                distance: 50.0,
+            })
+            .recipe(Recipe {
+                reqs: vec![(ResourceType::Wood, 10)],
+                product: ItemType::UndergroundExit,
+            }),
+        KnowledgeDef::new("Stone Machines")
+            .requirement(UnlockRequirement::TotalGathered {
+                resource: ResourceNodeType::Tree,
+                amount: 50,
+            })
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Food,
+                amount: 50,
+            }),
+        KnowledgeDef::new("Iron Machines")
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Wood,
+                amount: 200,
+            })
+            .requirement(UnlockRequirement::TotalDeposited {
+                resource: ResourceType::Water,
+                amount: 100,
+            }),
         KnowledgeDef::new("Plant Watering")
             .requirement(UnlockRequirement::TotalDeposited {
                 resource: ResourceType::Food,
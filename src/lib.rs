@@ -3,11 +3,14 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
+pub mod blueprint;
 pub mod consts;
 pub mod container;
 pub mod crafting;
 pub mod debug;
+pub mod energy_stats;
 pub mod ground_items;
+pub mod inventory;
 pub mod items;
 pub mod knowledge;
 pub mod machines;
@@ -16,6 +19,7 @@ pub mod notification;
 pub mod player;
 pub mod resources;
 pub mod sprites;
+pub mod tools;
 pub mod utils;
 pub mod village;
 pub mod weather;
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+use crate::{consts::Z_RESOURCES, map::TilePos, player::Targettable, sprites::EntitySprite};
+
+/// For battery machines at all times
+#[derive(Bundle)]
+pub struct BatteryBundle {
+    machine_marker: Machine,
+    animation_sprites: AnimationSprites,
+    battery_marker: Battery,
+    capacity: BatteryCapacity,
+    rate: BatteryRate,
+    // Charge is kept while the battery is carried around
+    stored_energy: StoredEnergy,
+}
+impl BatteryBundle {
+    pub fn new(capacity: f32, rate: f32, sprites: Vec<EntitySprite>) -> Self {
+        Self {
+            machine_marker: Machine::Battery,
+            animation_sprites: AnimationSprites(sprites),
+            battery_marker: Battery,
+            capacity: BatteryCapacity(capacity),
+            rate: BatteryRate(rate),
+            stored_energy: StoredEnergy(0.),
+        }
+    }
+}
+
+/// For battery machines when placed down
+#[derive(Bundle)]
+pub struct PlacedBatteryBundle {
+    output_direction: Direction,
+    tile_pos: TilePos,
+    transform: Transform,
+    placed: Placed,
+    targettable: Targettable,
+}
+impl PlacedBatteryBundle {
+    pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
+        Self {
+            output_direction: Direction(direction),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
+            tile_pos,
+            placed: Placed,
+            targettable: Targettable,
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+/// Marker for battery machines
+#[derive(Component)]
+pub struct Battery;
+
+/// Maximum amount of energy a battery can hold. Units match `PowerProduction` * seconds.
+#[derive(Component)]
+pub struct BatteryCapacity(pub f32);
+
+/// Maximum power a battery can charge or discharge at per second.
+#[derive(Component)]
+pub struct BatteryRate(pub f32);
+
+/// Amount of energy currently held in a battery.
+#[derive(Component, Default)]
+pub struct StoredEnergy(pub f32);
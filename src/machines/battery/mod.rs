@@ -0,0 +1,2 @@
+pub mod bundles;
+pub mod components;
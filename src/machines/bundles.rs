@@ -1,3 +1,5 @@
 pub use super::{
-    harvester::bundles::*, picker_upper::bundles::*, transporter::bundles::*, windmill::bundles::*,
+    battery::bundles::*, burner::bundles::*, harvester::bundles::*, picker_upper::bundles::*,
+    power_pole::bundles::*, transporter::bundles::*, underground_belt::bundles::*,
+    water_mill::bundles::*, windmill::bundles::*,
 };
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+use crate::{
+    consts::Z_RESOURCES, items::ItemType, map::TilePos, player::Targettable, sprites::EntitySprite,
+};
+
+/// For burner machines at all times (stateless parts of the prefab)
+#[derive(Bundle)]
+pub struct BurnerBundle {
+    machine_marker: Machine,
+    animation_sprites: AnimationSprites,
+    burner_marker: Burner,
+    output: BurnerOutput,
+    capacity: FuelCapacity,
+    accepts_items: AcceptsItems,
+    ports: Ports,
+    // Fuel is kept while the burner is carried around
+    fuel: FuelBuffer,
+    // Speed is just used for animation for burners
+    speed: MachineSpeed,
+}
+impl BurnerBundle {
+    pub fn new(output: f32, capacity: f32, speed: f32, sprites: Vec<EntitySprite>) -> Self {
+        Self {
+            machine_marker: Machine::Burner,
+            animation_sprites: AnimationSprites(sprites),
+            burner_marker: Burner,
+            output: BurnerOutput(output),
+            capacity: FuelCapacity(capacity),
+            accepts_items: AcceptsItems::Whitelist(ItemType::FUELS.to_vec()),
+            ports: Ports::new(Face::SIDES, []),
+            fuel: FuelBuffer(0.),
+            speed: MachineSpeed(speed),
+        }
+    }
+}
+
+/// For burner machines when placed down (stateful / instance-specific components)
+#[derive(Bundle)]
+pub struct PlacedBurnerBundle {
+    output_direction: Direction,
+    tile_pos: TilePos,
+    transform: Transform,
+    placed: Placed,
+    targettable: Targettable,
+    current_production: PowerProduction,
+    // State is just used for animation for burners
+    state: MachineState,
+}
+impl PlacedBurnerBundle {
+    pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
+        Self {
+            output_direction: Direction(direction),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
+            tile_pos,
+            placed: Placed,
+            targettable: Targettable,
+            current_production: PowerProduction(0.0),
+            state: MachineState(0.),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+use super::super::components::Wear;
+
+/// Marker for burner machines, which generate power by burning fuel items
+#[derive(Component)]
+#[require(Wear)]
+pub struct Burner;
+
+/// Power produced per second while the burner has fuel
+#[derive(Component)]
+pub struct BurnerOutput(pub f32);
+
+/// Seconds of burn time left in the burner
+#[derive(Component, Default)]
+pub struct FuelBuffer(pub f32);
+
+/// Maximum seconds of burn time the burner will take in. It stops accepting fuel when full.
+#[derive(Component)]
+pub struct FuelCapacity(pub f32);
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+use crate::items::ItemType;
+
+/// Burn fuel in all placed burners, producing a steady amount of power while there's fuel left.
+pub fn tick_burners(
+    burners: Query<
+        (
+            Entity,
+            &mut FuelBuffer,
+            &FuelCapacity,
+            &BurnerOutput,
+            &mut PowerProduction,
+            &MachineSpeed,
+            &mut MachineState,
+            &mut Wear,
+            Has<AcceptsItems>,
+        ),
+        (With<Burner>, With<Placed>),
+    >,
+    timer: Res<Time>,
+    mut commands: Commands,
+) {
+    for (
+        burner,
+        mut fuel,
+        capacity,
+        output,
+        mut current_energy,
+        speed,
+        mut state,
+        mut wear,
+        accepting,
+    ) in burners
+    {
+        // Burn fuel, as long as the burner hasn't broken down
+        if fuel.0 > 0. && !wear.is_broken() {
+            current_energy.0 = output.0 * wear.efficiency();
+            fuel.0 = (fuel.0 - timer.delta_secs()).max(0.);
+        } else {
+            current_energy.0 = 0.;
+        }
+
+        // Only take in more fuel when there's space for it
+        let full = fuel.0 >= capacity.0;
+        if full && accepting {
+            commands.entity(burner).remove::<AcceptsItems>();
+        } else if !full && !accepting {
+            commands
+                .entity(burner)
+                .insert(AcceptsItems::Whitelist(ItemType::FUELS.to_vec()));
+        }
+
+        // Update animation, wearing the machine down with each full turn
+        let produced = current_energy.0 * timer.delta_secs();
+        state.0 += produced;
+        if state.0 >= speed.0 {
+            state.0 %= speed.0;
+            wear.add_action();
+        }
+    }
+}
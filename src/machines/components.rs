@@ -4,19 +4,35 @@ use bevy::{platform::collections::HashMap, prelude::*};
 
 use super::bundles::*;
 pub use super::{
-    harvester::components::*, network::components::*, picker_upper::components::*,
-    transporter::components::*, windmill::components::*,
+    battery::components::*, burner::components::*, harvester::components::*,
+    network::components::*, picker_upper::components::*, power_pole::components::*,
+    transporter::components::*, underground_belt::components::*, water_mill::components::*,
+    windmill::components::*,
+};
+use crate::{
+    consts::{MACHINE_REPAIR_COST, MACHINE_WEAR_PER_ACTION, MACHINE_WEAR_SLOWDOWN},
+    ground_items::GroundItemBundle,
+    items::ItemType,
+    map::TilePos,
+    resources::ResourceType,
+    sprites::EntitySprite,
+    utils::query::LUTParam,
 };
-use crate::{items::ItemType, map::TilePos, sprites::EntitySprite, utils::query::LUTParam};
 
 /// Marker for machines, also machine type
 #[derive(Component, Debug)]
+#[require(MachineTier, Footprint)]
 pub enum Machine {
     VillageCentre,
     Harvester,
     Transporter,
     PickerUpper,
     Windmill,
+    Battery,
+    PowerPole,
+    WaterMill,
+    Burner,
+    UndergroundBelt,
 }
 
 impl Machine {
@@ -36,6 +52,21 @@ impl Machine {
             Windmill => {
                 commands.insert(PlacedWindmillBundle::new(pos, direction));
             }
+            Battery => {
+                commands.insert(PlacedBatteryBundle::new(pos, direction));
+            }
+            PowerPole => {
+                commands.insert(PlacedPowerPoleBundle::new(pos, direction));
+            }
+            WaterMill => {
+                commands.insert(PlacedWaterMillBundle::new(pos, direction));
+            }
+            Burner => {
+                commands.insert(PlacedBurnerBundle::new(pos, direction));
+            }
+            UndergroundBelt => {
+                commands.insert(PlacedUndergroundBeltBundle::new(pos, direction));
+            }
             VillageCentre => unreachable!("Village centre cannot be placed"),
         }
     }
@@ -55,6 +86,21 @@ impl Machine {
             Windmill => {
                 commands.remove::<PlacedWindmillBundle>();
             }
+            Battery => {
+                commands.remove::<PlacedBatteryBundle>();
+            }
+            PowerPole => {
+                commands.remove::<PlacedPowerPoleBundle>();
+            }
+            WaterMill => {
+                commands.remove::<PlacedWaterMillBundle>();
+            }
+            Burner => {
+                commands.remove::<PlacedBurnerBundle>();
+            }
+            UndergroundBelt => {
+                commands.remove::<PlacedUndergroundBeltBundle>();
+            }
             VillageCentre => unreachable!("Village centre cannot be placed"),
         }
     }
@@ -80,14 +126,369 @@ impl AcceptsItems {
 #[derive(Component)]
 pub struct Direction(pub IVec2);
 
+impl Direction {
+    /// Tile on the given face of a machine at this position
+    pub fn face_tile(&self, tile_pos: &TilePos, footprint: &Footprint, face: Face) -> TilePos {
+        tile_pos + self.0.rotate(footprint.face_offset(face))
+    }
+
+    /// Turn an offset from a machine facing this way back round to how it'd be facing +X
+    pub fn unrotate(&self, offset: IVec2) -> IVec2 {
+        IVec2::new(self.0.x, -self.0.y).rotate(offset)
+    }
+}
+
+/// Rectangle of tiles covered by a machine, measured forwards (x) and to the left (y) of the way
+/// it's facing. The machine's `TilePos` is the back-right corner, so the footprint swings round it
+/// as the machine rotates.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Footprint(pub UVec2);
+impl Default for Footprint {
+    fn default() -> Self {
+        Self(UVec2::ONE)
+    }
+}
+impl Footprint {
+    /// Every tile covered by a machine at `tile_pos` facing `direction`
+    pub fn tiles(&self, tile_pos: TilePos, direction: IVec2) -> impl Iterator<Item = TilePos> {
+        let size = self.0.as_ivec2();
+        (0..size.y).flat_map(move |y| {
+            (0..size.x).map(move |x| tile_pos + direction.rotate(IVec2::new(x, y)))
+        })
+    }
+
+    /// Offset to the tile on a face, for a machine facing +X. Faces longer than one tile use the
+    /// tile level with the machine's corner.
+    pub fn face_offset(&self, face: Face) -> IVec2 {
+        let size = self.0.as_ivec2();
+        match face {
+            Face::Centre => IVec2::ZERO,
+            Face::Front => IVec2::new(size.x, 0),
+            Face::Right => IVec2::NEG_Y,
+            Face::Back => IVec2::NEG_X,
+            Face::Left => IVec2::new(0, size.y),
+        }
+    }
+
+    /// Face that the tile at `offset` from the machine's corner is on, for a machine facing +X.
+    /// Every tile along a side counts as that face.
+    pub fn face_at(&self, offset: IVec2) -> Option<Face> {
+        let size = self.0.as_ivec2();
+        let along_x = (0..size.x).contains(&offset.x);
+        let along_y = (0..size.y).contains(&offset.y);
+        match (offset.x, offset.y) {
+            _ if along_x && along_y => Some(Face::Centre),
+            (x, _) if x == size.x && along_y => Some(Face::Front),
+            (-1, _) if along_y => Some(Face::Back),
+            (_, -1) if along_x => Some(Face::Right),
+            (_, y) if y == size.y && along_x => Some(Face::Left),
+            _ => None,
+        }
+    }
+}
+
+/// Side of a machine, relative to the way it's facing
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Face {
+    /// The machine's own tile
+    Centre,
+    Front,
+    Right,
+    Back,
+    Left,
+}
+impl Face {
+    /// The four sides, clockwise from the front
+    pub const SIDES: [Self; 4] = [Self::Front, Self::Right, Self::Back, Self::Left];
+
+    /// Way this face points out of the machine, for a machine facing +X. This is also the offset
+    /// to the tile on this face for single tile machines.
+    pub fn offset(&self) -> IVec2 {
+        use Face::*;
+        match self {
+            Centre => IVec2::ZERO,
+            Front => IVec2::X,
+            Right => IVec2::NEG_Y,
+            Back => IVec2::NEG_X,
+            Left => IVec2::Y,
+        }
+    }
+
+    /// Next side going clockwise
+    pub fn clockwise(&self) -> Self {
+        use Face::*;
+        match self {
+            Centre => Centre,
+            Front => Right,
+            Right => Back,
+            Back => Left,
+            Left => Front,
+        }
+    }
+}
+
+/// Parse a face from its name, as written by `Debug`
+impl std::str::FromStr for Face {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Face::*;
+        match s {
+            "Centre" => Ok(Centre),
+            "Front" => Ok(Front),
+            "Right" => Ok(Right),
+            "Back" => Ok(Back),
+            "Left" => Ok(Left),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+/// Faces which a machine takes items from (inputs) and gives items to (outputs). Machines without
+/// ports take items from any side.
+#[derive(Component, Clone, Debug)]
+pub struct Ports {
+    pub inputs: Vec<Face>,
+    pub outputs: Vec<Face>,
+}
+impl Ports {
+    pub fn new(inputs: impl Into<Vec<Face>>, outputs: impl Into<Vec<Face>>) -> Self {
+        Self {
+            inputs: inputs.into(),
+            outputs: outputs.into(),
+        }
+    }
+
+    /// Whether an item given from the tile at `offset` from the machine's corner can go in
+    pub fn accepts_from(
+        &self,
+        direction: &Direction,
+        footprint: &Footprint,
+        offset: IVec2,
+    ) -> bool {
+        footprint
+            .face_at(direction.unrotate(offset))
+            .is_some_and(|face| self.inputs.contains(&face))
+    }
+
+    /// Move the first output round to the next side clockwise which isn't an input
+    pub fn rotate_output(&mut self) {
+        let Some(output) = self.outputs.first_mut() else {
+            return;
+        };
+
+        let mut face = output.clockwise();
+        while self.inputs.contains(&face) && face != *output {
+            face = face.clockwise();
+        }
+        *output = face;
+    }
+}
+
+/// Direction that the next machine will be placed facing
+#[derive(Resource)]
+pub struct PlacementDirection(pub IVec2);
+impl Default for PlacementDirection {
+    fn default() -> Self {
+        Self(IVec2::X)
+    }
+}
+
+/// Translucent preview of where the held machine will be placed
+#[derive(Component)]
+pub struct PlacementGhost {
+    /// Held machine being previewed
+    pub machine: Entity,
+}
+
 /// Maximum work rate of the machine when fully powered (actions per second).
 #[derive(Component)]
 pub struct MachineSpeed(pub f32);
 
-/// Maximum power the machine can consume per second. Units match `CurrentEnergy`.
+/// Maximum power the machine can consume per second while working. Units match
+/// `PowerProduction`.
 #[derive(Component)]
+#[require(PowerPriority, Wear)]
 pub struct PowerConsumption(pub f32);
 
+/// Build quality of a machine. Higher tiers work faster and use less power for each action.
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum MachineTier {
+    #[default]
+    Wooden,
+    Stone,
+    Iron,
+}
+impl MachineTier {
+    /// Tier this one can be upgraded to, if any
+    pub fn next(&self) -> Option<Self> {
+        use MachineTier::*;
+        match self {
+            Wooden => Some(Stone),
+            Stone => Some(Iron),
+            Iron => None,
+        }
+    }
+
+    /// Multiplier on the base `MachineSpeed`, and on how much generators & batteries put out
+    pub fn speed_multiplier(&self) -> f32 {
+        use MachineTier::*;
+        match self {
+            Wooden => 1.,
+            Stone => 1.5,
+            Iron => 2.,
+        }
+    }
+
+    /// Multiplier on the base power consumption. This grows slower than the speed, so each action
+    /// costs less power.
+    pub fn power_multiplier(&self) -> f32 {
+        use MachineTier::*;
+        match self {
+            Wooden => 1.,
+            Stone => 1.25,
+            Iron => 1.5,
+        }
+    }
+
+    /// Resources needed to upgrade a machine to this tier
+    pub fn upgrade_cost(&self) -> Vec<(ResourceType, usize)> {
+        use MachineTier::*;
+        match self {
+            Wooden => vec![],
+            Stone => vec![(ResourceType::Wood, 10), (ResourceType::Food, 5)],
+            Iron => vec![(ResourceType::Wood, 20), (ResourceType::Water, 10)],
+        }
+    }
+
+    /// Knowledge which needs to be unlocked before machines can be upgraded to this tier
+    pub fn knowledge(&self) -> Option<&'static str> {
+        use MachineTier::*;
+        match self {
+            Wooden => None,
+            Stone => Some("Stone Machines"),
+            Iron => Some("Iron Machines"),
+        }
+    }
+}
+
+/// Error types when an upgrade is attempted but fails
+#[derive(Debug)]
+pub enum FailedUpgradeReason {
+    NotUpgradeable,
+    MaxTier,
+    NotUnlocked,
+    NotEnoughResources,
+}
+
+/// Event thrown when a machine upgrade is attempted but fails
+#[derive(Event)]
+pub struct FailedUpgrade {
+    pub machine: String,
+    pub reason: FailedUpgradeReason,
+}
+
+/// How worn down a machine is from use.
+/// 0 - 1, where 1 is broken
+#[derive(Component, Default)]
+pub struct Wear(pub f32);
+impl Wear {
+    /// Wear the machine down after completing an action
+    pub fn add_action(&mut self) {
+        self.0 = (self.0 + MACHINE_WEAR_PER_ACTION).min(1.);
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.0 >= 1.
+    }
+
+    /// Multiplier on the machine's work rate. Machines slow down once they're past the slowdown
+    /// point, and stop entirely when broken.
+    pub fn efficiency(&self) -> f32 {
+        if self.is_broken() {
+            0.
+        } else {
+            1. - (self.0 - MACHINE_WEAR_SLOWDOWN).max(0.)
+        }
+    }
+
+    /// Resources needed to repair the machine back to new
+    pub fn repair_cost(&self) -> Vec<(ResourceType, usize)> {
+        let wood = (self.0 * MACHINE_REPAIR_COST as f32).ceil() as usize;
+        vec![(ResourceType::Wood, wood)]
+    }
+}
+
+/// Marker for machines which have worn out, and won't work until they're repaired
+#[derive(Component)]
+pub struct Broken;
+
+/// Event triggered when a machine wears out
+#[derive(Event)]
+pub struct MachineBrokeDown {
+    pub machine: String,
+}
+
+/// Error types when a repair is attempted but fails
+#[derive(Debug)]
+pub enum FailedRepairReason {
+    NotWorn,
+    NotEnoughResources,
+}
+
+/// Event thrown when a machine repair is attempted but fails
+#[derive(Event)]
+pub struct FailedRepair {
+    pub machine: String,
+    pub reason: FailedRepairReason,
+}
+
+/// Power the machine consumes per second just for being placed, even when it's not working.
+#[derive(Component)]
+pub struct IdlePowerConsumption(pub f32);
+
+/// Extra power the machine consumes per second for each item it's working on.
+#[derive(Component)]
+pub struct LoadPowerConsumption(pub f32);
+
+/// Order in which consumers are supplied when there isn't enough power to go around
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum PowerPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+impl PowerPriority {
+    /// Highest priority first
+    pub const ORDER: [Self; 3] = [Self::High, Self::Medium, Self::Low];
+
+    /// Cycle to the next priority level
+    pub fn next(&self) -> Self {
+        use PowerPriority::*;
+        match self {
+            Low => Medium,
+            Medium => High,
+            High => Low,
+        }
+    }
+}
+
+/// Parse a priority from its name, as written by `Debug`
+impl std::str::FromStr for PowerPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use PowerPriority::*;
+        match s {
+            "Low" => Ok(Low),
+            "Medium" => Ok(Medium),
+            "High" => Ok(High),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
 /// How much progress the machine has made towards completing one action.
 /// 0 - 1
 #[derive(Component, Default)]
@@ -104,7 +505,83 @@ impl Deref for MachineLUT {
     }
 }
 
-pub type Machines<'w, 's, Q, F> = LUTParam<'w, 's, MachineLUT, TilePos, Q, F>;
+pub type Machines<'w, 's, Q, F = ()> = LUTParam<'w, 's, MachineLUT, TilePos, Q, F>;
+
+/// Placed machines which can be given items
+pub type Receivers<'w, 's> = Machines<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Machine,
+        &'static AcceptsItems,
+        &'static TilePos,
+        Option<(&'static Ports, &'static Direction, &'static Footprint)>,
+    ),
+    With<Placed>,
+>;
+
+/// Find a machine on `target` which will take an item given from `source`
+pub fn find_receiver<'a>(
+    receivers: &'a Receivers,
+    target: &TilePos,
+    source: &TilePos,
+    item_type: &ItemType,
+) -> Option<(Entity, &'a Machine)> {
+    receivers
+        .get(target)
+        .filter(|(_, _, acceptable_items, anchor, ports)| {
+            // Faces are relative to the receiver's own tile, which isn't necessarily the one
+            // that was hit
+            acceptable_items.can_accept(item_type)
+                && ports.is_none_or(|(ports, direction, footprint)| {
+                    ports.accepts_from(direction, footprint, source.0 - anchor.0)
+                })
+        })
+        .map(|(machine, machine_type, ..)| (machine, machine_type))
+}
+
+/// Give a finished item to a machine on one of the output faces, or drop it on the ground in
+/// front of the first output
+pub fn output_item(
+    source_name: &str,
+    item: Entity,
+    item_type: &ItemType,
+    tile_pos: &TilePos,
+    direction: &Direction,
+    footprint: &Footprint,
+    ports: &Ports,
+    receivers: &Receivers,
+    commands: &mut Commands,
+    transfer_items: &mut MessageWriter<TransferItem>,
+) {
+    let output_tiles = ports
+        .outputs
+        .iter()
+        .map(|face| direction.face_tile(tile_pos, footprint, *face))
+        .collect::<Vec<_>>();
+
+    // Check if there's something beside it
+    if let Some((machine, machine_type)) = output_tiles
+        .iter()
+        .find_map(|output_pos| find_receiver(receivers, output_pos, tile_pos, item_type))
+    {
+        info!("Transferring item {} -> {:?}", source_name, machine_type);
+
+        // Request to transfer to the target machine
+        transfer_items.write(TransferItem {
+            item,
+            target_machine: machine,
+        });
+    } else {
+        info!("Transferring item {} -> ground", source_name);
+        // Drop item on ground
+        let output_pos = output_tiles.first().unwrap_or(tile_pos);
+        commands
+            .entity(item)
+            .insert(GroundItemBundle::new(&output_pos.as_world_pos()));
+    }
+}
 
 /// Sprites which are cycled through depending on the progress of the machine
 #[derive(Component)]
@@ -20,11 +20,14 @@ pub struct HarvesterBundle {
     harvestable_nodes: HarvestableNodes,
     speed: MachineSpeed,
     power_consumption: PowerConsumption,
+    idle_power_consumption: IdlePowerConsumption,
+    ports: Ports,
 }
 impl HarvesterBundle {
     pub fn new(
         speed: f32,
         power_consumption: f32,
+        idle_power_consumption: f32,
         harvestable_nodes: impl IntoIterator<Item = ResourceNodeType>,
         sprites: Vec<EntitySprite>,
     ) -> Self {
@@ -34,6 +37,9 @@ impl HarvesterBundle {
             harvestable_nodes: HarvestableNodes(HashSet::from_iter(harvestable_nodes)),
             speed: MachineSpeed(speed),
             power_consumption: PowerConsumption(power_consumption),
+            idle_power_consumption: IdlePowerConsumption(idle_power_consumption),
+            // Take from the front and output behind
+            ports: Ports::new([Face::Front], [Face::Back]),
             animation_sprites: AnimationSprites(sprites),
         }
     }
@@ -52,7 +58,9 @@ pub struct PlacedHarvesterBundle {
 impl PlacedHarvesterBundle {
     pub fn new(tile_pos: TilePos, output_direction: IVec2) -> Self {
         Self {
-            transform: tile_pos.as_transform(Z_RESOURCES),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(output_direction.as_vec2().to_angle())),
             tile_pos,
             output_direction: Direction(output_direction),
             state: MachineState(0.),
@@ -70,11 +78,14 @@ pub struct WaterWheelBundle {
     harvestable_terrain: HarvestableTerrain,
     speed: MachineSpeed,
     power_consumption: PowerConsumption,
+    idle_power_consumption: IdlePowerConsumption,
+    ports: Ports,
 }
 impl WaterWheelBundle {
     pub fn new(
         speed: f32,
         power_consumption: f32,
+        idle_power_consumption: f32,
         harvestable_terrain: impl IntoIterator<Item = TerrainSprite>,
         sprites: Vec<EntitySprite>,
     ) -> Self {
@@ -85,6 +96,9 @@ impl WaterWheelBundle {
             harvestable_terrain: HarvestableTerrain(HashSet::from_iter(harvestable_terrain)),
             speed: MachineSpeed(speed),
             power_consumption: PowerConsumption(power_consumption),
+            idle_power_consumption: IdlePowerConsumption(idle_power_consumption),
+            // Take from the water in front and output behind
+            ports: Ports::new([Face::Front], [Face::Back]),
         }
     }
 }
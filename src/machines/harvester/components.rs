@@ -1,6 +1,6 @@
 use bevy::{platform::collections::HashSet, prelude::*};
 
-use crate::{resources::ResourceNodeType, sprites::TerrainSprite};
+use crate::{map::TilePos, resources::ResourceNodeType, sprites::TerrainSprite};
 
 /// Marker for harvesting machines
 #[derive(Component)]
@@ -13,3 +13,19 @@ pub struct HarvestableNodes(pub HashSet<ResourceNodeType>);
 /// Types of terrain this machine can harvest
 #[derive(Component)]
 pub struct HarvestableTerrain(pub HashSet<TerrainSprite>);
+
+/// Rectangle of tiles in front of a harvester that it takes resources from, instead of just its
+/// input faces. Measured forwards (x) from the tile in front, and across (y) centred on it.
+#[derive(Component)]
+pub struct HarvestArea(pub UVec2);
+impl HarvestArea {
+    /// Every tile in the area for a harvester at `tile_pos` facing `direction`
+    pub fn tiles(&self, tile_pos: TilePos, direction: IVec2) -> impl Iterator<Item = TilePos> {
+        let size = self.0.as_ivec2();
+        let half_width = size.y / 2;
+        (1..=size.x).flat_map(move |x| {
+            (-half_width..size.y - half_width)
+                .map(move |y| tile_pos + direction.rotate(IVec2::new(x, y)))
+        })
+    }
+}
@@ -3,13 +3,78 @@ use bevy::prelude::*;
 use super::super::components::*;
 use crate::{
     consts::RESOURCE_PICKUP_AMOUNT,
-    ground_items::GroundItemBundle,
     items::ItemType,
     map::{Chunks, TerrainData, TilePos},
+    player::TargettedBy,
     resources::{ResourceAmount, ResourceMarker, ResourceNodeType, ResourceNodes},
-    sprites::{GetSprite, SpriteSheets},
+    sprites::{SpriteSheets, TerrainSprite},
 };
 
+/// Find a node this harvester can take from. Harvesters with an area take from the fullest node
+/// in it, others from the first input tile with a node.
+fn find_resource_node(
+    tile_pos: &TilePos,
+    direction: &Direction,
+    footprint: &Footprint,
+    ports: &Ports,
+    area: Option<&HarvestArea>,
+    harvestable_nodes: &HarvestableNodes,
+    resources: &ResourceNodes<
+        (&ResourceNodeType, &ItemType, &mut ResourceAmount),
+        With<ResourceMarker>,
+    >,
+) -> Option<TilePos> {
+    // Nodes with some left to harvest, which can be harvested by this machine
+    let harvestable = |resource_pos: &TilePos| {
+        resources
+            .get(resource_pos)
+            .filter(|(resource_type, _, resource_amount)| {
+                resource_amount.0 > 0 && harvestable_nodes.0.contains(*resource_type)
+            })
+            .map(|(_, _, resource_amount)| resource_amount.0)
+    };
+
+    if let Some(area) = area {
+        area.tiles(*tile_pos, direction.0)
+            .filter_map(|resource_pos| {
+                harvestable(&resource_pos).map(|amount| (resource_pos, amount))
+            })
+            .max_by_key(|(_, amount)| *amount)
+            .map(|(resource_pos, _)| resource_pos)
+    } else {
+        ports
+            .inputs
+            .iter()
+            .map(|face| direction.face_tile(tile_pos, footprint, *face))
+            .find(|resource_pos| harvestable(resource_pos).is_some())
+    }
+}
+
+/// Find the first input tile with terrain this harvester can take from
+fn find_terrain(
+    tile_pos: &TilePos,
+    direction: &Direction,
+    footprint: &Footprint,
+    ports: &Ports,
+    harvestable_terrain: &HarvestableTerrain,
+    chunks: &Chunks<&TerrainData>,
+) -> Option<TerrainSprite> {
+    ports
+        .inputs
+        .iter()
+        .map(|face| direction.face_tile(tile_pos, footprint, *face))
+        .map(|resource_pos| {
+            // Get terrain on the input face
+            let (chunk_pos, offset) = resource_pos.to_chunk_offset();
+            let terrain_data = chunks.get(&chunk_pos).expect("Chunk data not generated");
+            terrain_data.0[offset.y as usize][offset.x as usize]
+        })
+        // Check that terrain can be harvested by this machine, and that it produces something
+        .find(|terrain_type| {
+            harvestable_terrain.0.contains(terrain_type) && terrain_type.item_type().is_some()
+        })
+}
+
 pub fn precheck_resource_harvesters(
     harvesters: Query<
         (
@@ -17,51 +82,56 @@ pub fn precheck_resource_harvesters(
             &mut MachineState,
             &PowerConsumption,
             &Direction,
+            &Footprint,
+            &Ports,
+            Option<&HarvestArea>,
             &HarvestableNodes,
         ),
-        With<Harvester>,
+        (With<Harvester>, Without<Broken>),
+    >,
+    resources: ResourceNodes<
+        (&ResourceNodeType, &ItemType, &mut ResourceAmount),
+        With<ResourceMarker>,
     >,
-    resources: ResourceNodes<(&ResourceNodeType, &ResourceAmount), With<ResourceMarker>>,
     mut energy_networks: ResMut<EnergyNetworks>,
 ) {
-    for (tile_pos, mut state, power, direction, harvestable_nodes) in harvesters {
-        let resource_pos = tile_pos + direction.0;
-
-        // Check if there's a harvestable node in front of the machine
-        let Some((resource_type, resource_amount)) = resources.get(&resource_pos) else {
-            // No resource, so reset progress
-            state.0 = 0.;
-            continue;
-        };
-
-        // Check that there's some left to harvest
-        if resource_amount.0 == 0 {
-            // Resource is depleted, so reset progress
-            state.0 = 0.;
-            continue;
-        }
-
-        // Check that resource can be harvested by this machine
-        if !harvestable_nodes.0.contains(resource_type) {
-            // Can't harvest this type of node, so reset progress
+    for (tile_pos, mut state, power, direction, footprint, ports, area, harvestable_nodes) in
+        harvesters
+    {
+        // Check if there's a harvestable node in reach
+        if find_resource_node(
+            tile_pos,
+            direction,
+            footprint,
+            ports,
+            area,
+            harvestable_nodes,
+            &resources,
+        )
+        .is_none()
+        {
+            // Nothing to harvest, so reset progress
             state.0 = 0.;
             continue;
         }
 
         // Register power demand for this machine
-        energy_networks.power_demands.insert(*tile_pos, power.0);
+        energy_networks.request_power(*tile_pos, power.0, 0.);
     }
 }
 
-/// Advance the state of the harvesters if there is a resource beside it
+/// Advance the state of the harvesters if there is a resource on an input face
 pub fn tick_resource_harvesters(
     harvesters: Query<
         (
             &TilePos,
             &mut MachineState,
             &MachineSpeed,
-            &PowerConsumption,
+            &mut Wear,
             &Direction,
+            &Footprint,
+            &Ports,
+            Option<&HarvestArea>,
             &HarvestableNodes,
         ),
         With<Harvester>,
@@ -70,46 +140,45 @@ pub fn tick_resource_harvesters(
         (&ResourceNodeType, &ItemType, &mut ResourceAmount),
         With<ResourceMarker>,
     >,
-    machines: Machines<(Entity, &Machine, &AcceptsItems), With<Placed>>,
+    receivers: Receivers,
     energy_networks: Res<EnergyNetworks>,
     timer: Res<Time>,
     sprite_sheets: Res<SpriteSheets>,
     mut commands: Commands,
     mut transfer_items: MessageWriter<TransferItem>,
 ) {
-    for (tile_pos, mut state, speed, power, direction, harvestable_nodes) in harvesters {
+    for (
+        tile_pos,
+        mut state,
+        speed,
+        mut wear,
+        direction,
+        footprint,
+        ports,
+        area,
+        harvestable_nodes,
+    ) in harvesters
+    {
         // TODO: These pre-checks have already been done for power calculations
 
-        // Check if there's a harvestable node in front of the machine
-        let resource_pos = tile_pos + direction.0;
-        let Some((resource_type, item_type, mut amount)) = resources.get_mut(&resource_pos) else {
-            // No resource, so reset progress
+        // Check if there's a harvestable node in reach
+        let Some(resource_pos) = find_resource_node(
+            tile_pos,
+            direction,
+            footprint,
+            ports,
+            area,
+            harvestable_nodes,
+            &resources,
+        ) else {
+            // Nothing to harvest, so reset progress
             state.0 = 0.;
             continue;
         };
 
-        // Check that the node has some resource left in it
-        if amount.0 == 0 {
-            // Resource is depleted, so reset progress
-            state.0 = 0.;
-            continue;
-        }
-
-        // Check that resource can be harvested by this machine
-        if !harvestable_nodes.0.contains(resource_type) {
-            // Can't harvest this type of node, so reset progress
-            state.0 = 0.;
-            continue;
-        }
-
-        let energy_supply = energy_networks
-            .power_provided
-            .get(tile_pos)
-            .expect("No power provided for this machine!");
-
-        // Calculate work rate at current power level
-        let satisfaction = (energy_supply / power.0).min(1.);
-        let work_rate = speed.0 * satisfaction;
+        // Calculate work rate at current power level, slowed down by wear
+        let satisfaction = energy_networks.satisfaction(tile_pos);
+        let work_rate = speed.0 * satisfaction * wear.efficiency();
 
         // Advance progress (1.0 == one completed action)
         state.0 += work_rate * timer.delta_secs();
@@ -120,35 +189,29 @@ pub fn tick_resource_harvesters(
             continue;
         }
         state.0 -= 1.0;
+        wear.add_action();
 
-        // Spawn an item
-        let output_pos = tile_pos - direction.0;
-        let item = commands.spawn(*item_type).id();
-        item_type.spawn_sprite(&mut commands, &sprite_sheets, Some(item));
-
-        // Check if there's something beside it
-        if let Some((machine, machine_type, acceptable_items)) = machines.get(&output_pos)
-            && acceptable_items.can_accept(item_type)
-        {
-            info!("Transferring item Harvester -> {:?}", machine_type);
-
-            // Request to transfer to the target machine
-            transfer_items.write(TransferItem {
-                item,
-                target_machine: machine,
-            });
-        } else {
-            info!("Transferring item Harvester -> ground");
-            // Drop item on ground
-            commands
-                .entity(item)
-                .insert(GroundItemBundle::new(&output_pos.as_world_pos()));
-        }
+        let (_, item_type, mut amount) = resources
+            .get_mut(&resource_pos)
+            .expect("Resource node was just found");
 
-        // Decrement resource amount now that we've produced an item.
-        // TODO: Spawn same number of items that were picked up
+        // Take from the node, and spawn a stack of what was taken
         let pickup_amount = RESOURCE_PICKUP_AMOUNT.min(amount.0);
         amount.0 -= pickup_amount;
+
+        let item = item_type.spawn_stack(&mut commands, &sprite_sheets, pickup_amount);
+        output_item(
+            "Harvester",
+            item,
+            item_type,
+            tile_pos,
+            direction,
+            footprint,
+            ports,
+            &receivers,
+            &mut commands,
+            &mut transfer_items,
+        );
     }
 }
 
@@ -159,38 +222,35 @@ pub fn precheck_terrain_harvesters(
             &mut MachineState,
             &PowerConsumption,
             &Direction,
+            &Footprint,
+            &Ports,
             &HarvestableTerrain,
         ),
-        With<Harvester>,
+        (With<Harvester>, Without<Broken>),
     >,
     chunks: Chunks<&TerrainData>,
     mut energy_networks: ResMut<EnergyNetworks>,
 ) {
-    for (tile_pos, mut state, power, direction, harvestable_terrain) in harvesters {
-        // Check if there's a harvestable node under of the machine
-        let resource_pos = tile_pos + direction.0;
-
-        // Get terrain under the machine
-        let (chunk_pos, offset) = resource_pos.to_chunk_offset();
-        let terrain_data = chunks.get(&chunk_pos).expect("Chunk data not generated");
-        let terrain_type = &terrain_data.0[offset.y as usize][offset.x as usize];
-
-        // Check that resource can be harvested by this machine
-        if !harvestable_terrain.0.contains(terrain_type) {
-            // Can't harvest this type of node, so reset progress
+    for (tile_pos, mut state, power, direction, footprint, ports, harvestable_terrain) in harvesters
+    {
+        // Check if there's harvestable terrain on one of the input faces
+        if find_terrain(
+            tile_pos,
+            direction,
+            footprint,
+            ports,
+            harvestable_terrain,
+            &chunks,
+        )
+        .is_none()
+        {
+            // Nothing to harvest, so reset progress
             state.0 = 0.;
             continue;
         }
 
-        // Check that the terrain can produce something
-        if terrain_type.item_type().is_none() {
-            // No item given
-            state.0 = 0.;
-            continue;
-        };
-
         // Register power demand for this machine
-        energy_networks.power_demands.insert(*tile_pos, power.0);
+        energy_networks.request_power(*tile_pos, power.0, 0.);
     }
 }
 
@@ -201,54 +261,45 @@ pub fn tick_terrain_harvesters(
             &TilePos,
             &mut MachineState,
             &MachineSpeed,
-            &PowerConsumption,
+            &mut Wear,
             &Direction,
+            &Footprint,
+            &Ports,
             &HarvestableTerrain,
         ),
         With<Harvester>,
     >,
     chunks: Chunks<&TerrainData>,
-    machines: Machines<(Entity, &Machine, &AcceptsItems), With<Placed>>,
+    receivers: Receivers,
     energy_networks: Res<EnergyNetworks>,
     timer: Res<Time>,
     sprite_sheets: Res<SpriteSheets>,
     mut commands: Commands,
     mut transfer_items: MessageWriter<TransferItem>,
 ) {
-    for (tile_pos, mut state, speed, power, direction, harvestable_terrain) in harvesters {
+    for (tile_pos, mut state, speed, mut wear, direction, footprint, ports, harvestable_terrain) in
+        harvesters
+    {
         // TODO: These pre-checks have already been done for power calculations
 
-        // Check if there's a harvestable node under of the machine
-        let resource_pos = tile_pos + direction.0;
-
-        // Get terrain under the machine
-        let (chunk_pos, offset) = resource_pos.to_chunk_offset();
-        let terrain_data = chunks.get(&chunk_pos).expect("Chunk data not generated");
-        let terrain_type = &terrain_data.0[offset.y as usize][offset.x as usize];
-
-        // Check that resource can be harvested by this machine
-        if !harvestable_terrain.0.contains(terrain_type) {
-            // Can't harvest this type of node, so reset progress
-            state.0 = 0.;
-            continue;
-        }
-
-        // Check that the terrain can produce something
-        let Some(item_type) = terrain_type.item_type() else {
-            // No item given
+        // Check if there's harvestable terrain on one of the input faces
+        let Some(item_type) = find_terrain(
+            tile_pos,
+            direction,
+            footprint,
+            ports,
+            harvestable_terrain,
+            &chunks,
+        )
+        .and_then(|terrain_type| terrain_type.item_type()) else {
+            // Nothing to harvest, so reset progress
             state.0 = 0.;
             continue;
         };
 
-        // Accumulate energy produced by adjacent machines (e.g., windmills)
-        let energy_supply = energy_networks
-            .power_provided
-            .get(tile_pos)
-            .expect("No power provided for this machine!");
-
-        // Calculate work rate at current power level
-        let satisfaction = (energy_supply / power.0).min(1.0);
-        let work_rate = speed.0 * satisfaction;
+        // Calculate work rate at current power level, slowed down by wear
+        let satisfaction = energy_networks.satisfaction(tile_pos);
+        let work_rate = speed.0 * satisfaction * wear.efficiency();
 
         // Tick the machine (scaled by available adjacent energy)
         state.0 += work_rate * timer.delta_secs();
@@ -259,29 +310,37 @@ pub fn tick_terrain_harvesters(
             continue;
         }
         state.0 -= 1.0;
+        wear.add_action();
+
+        // Spawn a stack of items
+        let item = item_type.spawn_stack(&mut commands, &sprite_sheets, RESOURCE_PICKUP_AMOUNT);
+        output_item(
+            "Harvester",
+            item,
+            &item_type,
+            tile_pos,
+            direction,
+            footprint,
+            ports,
+            &receivers,
+            &mut commands,
+            &mut transfer_items,
+        );
+    }
+}
 
-        // Spawn an item
-        let output_pos = tile_pos + direction.0;
-        let item = commands.spawn(item_type).id();
-        item_type.spawn_sprite(&mut commands, &sprite_sheets, Some(item));
-
-        // Check if there's something beside it
-        if let Some((machine, machine_type, acceptable_items)) = machines.get(&output_pos)
-            && acceptable_items.can_accept(&item_type)
-        {
-            info!("Transferring item Harvester -> {:?}", machine_type);
-
-            // Request to transfer to the target machine
-            transfer_items.write(TransferItem {
-                item,
-                target_machine: machine,
-            });
-        } else {
-            info!("Transferring item Harvester -> ground");
-            // Drop item on ground
-            commands
-                .entity(item)
-                .insert(GroundItemBundle::new(&output_pos.as_world_pos()));
+/// Outline the area that the targetted harvester takes resources from
+pub fn draw_targetted_harvest_area(
+    harvesters: Query<(&TilePos, &Direction, &HarvestArea), (With<Placed>, With<TargettedBy>)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile_pos, direction, area) in harvesters {
+        for tile in area.tiles(*tile_pos, direction.0) {
+            gizmos.rect_2d(
+                tile.as_world_pos().0,
+                Vec2::splat(0.9),
+                Color::srgba(0.2, 0.8, 0.2, 0.6),
+            );
         }
     }
 }
@@ -1,10 +1,15 @@
+pub mod battery;
 pub mod bundles;
+pub mod burner;
 mod components;
 pub mod harvester;
 pub mod network;
 pub mod picker_upper;
+pub mod power_pole;
 pub mod systems;
 pub mod transporter;
+pub mod underground_belt;
+pub mod water_mill;
 pub mod windmill;
 
 use bevy::prelude::*;
@@ -12,24 +17,27 @@ pub use bundles::*;
 pub use components::*;
 use systems::*;
 
-use crate::utils::run_if::{empty_hands, key_just_pressed};
+use crate::utils::run_if::{cursor_over_target, empty_hands, key_just_pressed, mouse_just_pressed};
 
 pub struct MachinePlugin;
 impl Plugin for MachinePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MachineLUT>()
             .init_resource::<EnergyNetworks>()
+            .init_resource::<PlacementDirection>()
             .add_message::<TransferItem>()
+            .add_message::<MachinePlacement>()
             .add_systems(
                 FixedUpdate,
                 (
-                    compute_energy_networks,
-                    (tick_windmills,),
+                    update_energy_networks,
+                    (tick_windmills, tick_water_mills, tick_burners),
                     produce_energy,
                     // Reset power demands before re-calculating
                     |mut networks: ResMut<EnergyNetworks>| {
                         networks.power_demands.clear();
                     },
+                    request_idle_power,
                     (
                         precheck_resource_harvesters,
                         precheck_terrain_harvesters,
@@ -41,6 +49,7 @@ impl Plugin for MachinePlugin {
                         tick_resource_harvesters,
                         tick_terrain_harvesters,
                         tick_transporters,
+                        tick_underground_belts,
                         tick_pickeruppers,
                     ),
                     transfer_items,
@@ -52,8 +61,35 @@ impl Plugin for MachinePlugin {
                 (
                     (place_machine, pickup_machine.run_if(empty_hands))
                         .run_if(key_just_pressed(KeyCode::KeyP)),
-                    rotate_machine.run_if(key_just_pressed(KeyCode::KeyR)),
+                    // Click to place at the cursor, or pick up the machine under it
+                    (
+                        place_machine,
+                        pickup_machine.run_if(empty_hands.and(cursor_over_target)),
+                    )
+                        .run_if(mouse_just_pressed(MouseButton::Left)),
+                    // Rotate the placement preview while holding a machine
+                    rotate_machine.run_if(
+                        key_just_pressed(KeyCode::KeyR)
+                            .and(not(any_with_component::<PlacementGhost>)),
+                    ),
+                    rotate_placement.run_if(
+                        key_just_pressed(KeyCode::KeyR).and(any_with_component::<PlacementGhost>),
+                    ),
+                    update_placement_ghost,
+                    cycle_power_priority.run_if(key_just_pressed(KeyCode::KeyO)),
+                    cycle_output_face.run_if(key_just_pressed(KeyCode::KeyF)),
+                    cycle_pickup_filter.run_if(key_just_pressed(KeyCode::KeyI)),
+                    upgrade_machine.run_if(key_just_pressed(KeyCode::KeyU)),
+                    repair_machine.run_if(key_just_pressed(KeyCode::KeyM)),
+                    break_worn_machines,
+                    draw_targetted_ports,
+                    draw_targetted_harvest_area,
+                    draw_targetted_pickup_radius,
+                    draw_machine_tiers,
+                    draw_machine_footprints,
+                    draw_machine_wear,
                     animate_machine,
+                    draw_power_wires,
                 ),
             );
     }
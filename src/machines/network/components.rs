@@ -1,3 +1,5 @@
+use std::cmp::Reverse;
+
 use bevy::{
     platform::collections::{HashMap, HashSet},
     prelude::*,
@@ -7,19 +9,286 @@ use crate::map::TilePos;
 
 /// Resource containing connected networks of placed machines.
 ///
-/// `networks` contains each network as a `HashSet<TilePos>` (4-connected).
-/// `membership` maps a `TilePos` to the index in `networks` for quick lookup
-/// of which network a tile belongs to.
+/// `networks` contains each network as a `HashSet<TilePos>` (4-connected, plus any power pole
+/// wires), keyed by a network ID. `membership` maps a `TilePos` to the ID in `networks` for quick
+/// lookup of which network a tile belongs to.
+///
+/// Machines are stored by their own `TilePos`. Machines covering more than one tile are connected
+/// to anything touching any of their edge tiles.
+///
+/// Networks are updated incrementally as machines are placed & removed, so IDs stay the same
+/// between ticks unless networks are merged or split.
 #[derive(Resource, Default)]
 pub struct EnergyNetworks {
     /// Connected components
-    pub networks: Vec<HashSet<TilePos>>,
+    pub networks: HashMap<usize, HashSet<TilePos>>,
     /// LUT for network membership
     pub membership: HashMap<TilePos, usize>,
+    /// Wires between power poles, stored in both directions
+    pub wires: HashMap<TilePos, HashSet<TilePos>>,
+    /// Tiles covered by each machine
+    pub footprints: HashMap<TilePos, Vec<TilePos>>,
+    /// Machine covering each tile
+    pub anchors: HashMap<TilePos, TilePos>,
+    /// ID to give to the next new network
+    pub next_id: usize,
     /// Requested power from consumer machines
-    pub power_demands: HashMap<TilePos, f32>,
+    pub power_demands: HashMap<TilePos, PowerDemand>,
     /// Total power available to each network
-    pub power_available: Vec<f32>,
+    pub power_available: HashMap<usize, f32>,
     /// Power made available to requesting machines
     pub power_provided: HashMap<TilePos, f32>,
+    /// Power flowing into (positive) or out of (negative) each battery
+    pub battery_flow: HashMap<TilePos, f32>,
+}
+impl EnergyNetworks {
+    /// Whether a consumer is getting less power than it asked for
+    pub fn is_starved(&self, tile_pos: &TilePos) -> bool {
+        self.power_demands.get(tile_pos).is_some_and(|demand| {
+            *self.power_provided.get(tile_pos).unwrap_or(&0.) < demand.total()
+        })
+    }
+
+    /// Fraction of its requested power that a consumer is getting, 0 - 1
+    pub fn satisfaction(&self, tile_pos: &TilePos) -> f32 {
+        let Some(demand) = self.power_demands.get(tile_pos) else {
+            // Not asking for anything, so can't do any work
+            return 0.;
+        };
+
+        if demand.total() == 0. {
+            1.
+        } else {
+            (self.power_provided.get(tile_pos).unwrap_or(&0.) / demand.total()).min(1.)
+        }
+    }
+
+    /// Register the power a consumer needs to do its work this tick, on top of its idle draw
+    pub fn request_power(&mut self, tile_pos: TilePos, active: f32, load: f32) {
+        let demand = self.power_demands.entry(tile_pos).or_default();
+        demand.active = active;
+        demand.load = load;
+    }
+
+    /// Demand, supply & battery flow summed up over the machines in each network
+    pub fn network_totals(&self) -> HashMap<usize, NetworkTotals> {
+        let mut totals = HashMap::<usize, NetworkTotals>::new();
+        for (tile_pos, demand) in &self.power_demands {
+            if let Some(network) = self.membership.get(tile_pos) {
+                let network_totals = totals.entry(*network).or_default();
+                network_totals.demand += *demand;
+                network_totals.provided += self.power_provided.get(tile_pos).unwrap_or(&0.);
+            }
+        }
+        for (tile_pos, flow) in &self.battery_flow {
+            if let Some(network) = self.membership.get(tile_pos) {
+                totals.entry(*network).or_default().battery_flow += flow;
+            }
+        }
+        totals
+    }
+
+    /// Each wire between power poles, listed once
+    pub fn links(&self) -> impl Iterator<Item = (TilePos, TilePos)> {
+        self.wires.iter().flat_map(|(from, tos)| {
+            tos.iter()
+                .filter(|to| from.0.to_array() < to.0.to_array())
+                .map(|to| (*from, *to))
+        })
+    }
+
+    /// Tiles that a machine at this position is connected to, whether there's a machine there
+    /// or not
+    fn neighbours(&self, tile_pos: TilePos) -> impl Iterator<Item = TilePos> {
+        let wired = self.wires.get(&tile_pos).into_iter().flatten().copied();
+
+        // Tiles touching the outside of the machine, mapped to the machines covering them
+        let footprint = self
+            .footprints
+            .get(&tile_pos)
+            .cloned()
+            .unwrap_or_else(|| vec![tile_pos]);
+        footprint
+            .clone()
+            .into_iter()
+            .flat_map(|tile| tile.adjacent())
+            .filter(move |tile| !footprint.contains(tile))
+            .map(|tile| self.anchors.get(&tile).copied().unwrap_or(tile))
+            .chain(wired)
+    }
+
+    /// Create a new empty network, returning its ID
+    fn new_network(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.networks.insert(id, HashSet::new());
+
+        id
+    }
+
+    /// Add a machine covering `footprint` to the networks, wired up to the given power poles. Any
+    /// networks it touches are merged into the biggest one.
+    pub fn add_machine(
+        &mut self,
+        tile_pos: TilePos,
+        footprint: impl IntoIterator<Item = TilePos>,
+        wired: impl IntoIterator<Item = TilePos>,
+    ) {
+        if self.membership.contains_key(&tile_pos) {
+            // Already in a network
+            return;
+        }
+
+        let footprint = footprint.into_iter().collect::<Vec<_>>();
+        for tile in &footprint {
+            self.anchors.insert(*tile, tile_pos);
+        }
+        self.footprints.insert(tile_pos, footprint);
+
+        for other in wired {
+            self.wires.entry(tile_pos).or_default().insert(other);
+            self.wires.entry(other).or_default().insert(tile_pos);
+        }
+
+        // Find the networks this machine joins together
+        let touching = self
+            .neighbours(tile_pos)
+            .filter_map(|neighbour| self.membership.get(&neighbour).copied())
+            .collect::<HashSet<_>>();
+
+        // Biggest network keeps its ID so as little as possible changes
+        let id = touching
+            .iter()
+            .copied()
+            .max_by_key(|id| (self.networks[id].len(), Reverse(*id)))
+            .unwrap_or_else(|| self.new_network());
+
+        for other in touching.into_iter().filter(|other| *other != id) {
+            let tiles = self
+                .networks
+                .remove(&other)
+                .expect("Membership points to a network which doesn't exist");
+
+            for tile in &tiles {
+                self.membership.insert(*tile, id);
+            }
+            self.networks
+                .get_mut(&id)
+                .expect("Network was just looked up")
+                .extend(tiles);
+        }
+
+        self.membership.insert(tile_pos, id);
+        self.networks
+            .get_mut(&id)
+            .expect("Network was just looked up")
+            .insert(tile_pos);
+    }
+
+    /// Remove a machine from the networks. If this cuts its network in two, the biggest piece
+    /// keeps the old ID.
+    pub fn remove_machine(&mut self, tile_pos: TilePos) {
+        let Some(id) = self.membership.remove(&tile_pos) else {
+            // Not in a network
+            return;
+        };
+
+        let mut network = self
+            .networks
+            .remove(&id)
+            .expect("Membership points to a network which doesn't exist");
+        network.remove(&tile_pos);
+
+        // Remember what it was connected to before cutting its wires
+        let neighbours = self
+            .neighbours(tile_pos)
+            .filter(|neighbour| network.contains(neighbour))
+            .collect::<Vec<_>>();
+
+        for tile in self.footprints.remove(&tile_pos).into_iter().flatten() {
+            self.anchors.remove(&tile);
+        }
+        for other in self.wires.remove(&tile_pos).into_iter().flatten() {
+            if let Some(wires) = self.wires.get_mut(&other) {
+                wires.remove(&tile_pos);
+            }
+        }
+
+        // Flood fill outwards from each of the neighbours to find what's still connected
+        let mut pieces = Vec::<HashSet<_>>::new();
+        for start in neighbours {
+            if pieces.iter().any(|piece| piece.contains(&start)) {
+                // Already reached from another neighbour
+                continue;
+            }
+
+            let mut stack = vec![start];
+            let mut piece = HashSet::from_iter([start]);
+            while let Some(cur) = stack.pop() {
+                for neighbour in self.neighbours(cur) {
+                    if network.contains(&neighbour) && piece.insert(neighbour) {
+                        stack.push(neighbour);
+                    }
+                }
+            }
+
+            pieces.push(piece);
+        }
+
+        // Biggest piece keeps the old ID, the rest become new networks
+        pieces.sort_by_key(|piece| Reverse(piece.len()));
+        let mut pieces = pieces.into_iter();
+        if let Some(biggest) = pieces.next() {
+            self.networks.insert(id, biggest);
+        }
+        for piece in pieces {
+            let new_id = self.new_network();
+            for tile in &piece {
+                self.membership.insert(*tile, new_id);
+            }
+            self.networks.insert(new_id, piece);
+        }
+    }
+}
+
+/// Breakdown of the power a consumer is requesting this tick
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PowerDemand {
+    /// Drawn just for being placed
+    pub idle: f32,
+    /// Drawn while working
+    pub active: f32,
+    /// Drawn for the items being worked on
+    pub load: f32,
+}
+impl PowerDemand {
+    pub fn total(&self) -> f32 {
+        self.idle + self.active + self.load
+    }
+}
+
+impl std::ops::AddAssign for PowerDemand {
+    fn add_assign(&mut self, rhs: Self) {
+        self.idle += rhs.idle;
+        self.active += rhs.active;
+        self.load += rhs.load;
+    }
+}
+
+/// Power flowing through the machines of one network this tick
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NetworkTotals {
+    /// Requested by consumers
+    pub demand: PowerDemand,
+    /// Given to consumers
+    pub provided: f32,
+    /// Into (positive) or out of (negative) batteries
+    pub battery_flow: f32,
+}
+
+/// Message sent whenever a machine is placed down or picked up
+#[derive(Message, Clone, Copy, Debug)]
+pub enum MachinePlacement {
+    Placed(TilePos),
+    Removed(TilePos),
 }
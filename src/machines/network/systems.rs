@@ -1,55 +1,73 @@
-use bevy::{
-    platform::collections::{HashMap, HashSet},
-    prelude::*,
-};
+use bevy::{platform::collections::HashMap, prelude::*};
 
 use super::super::components::*;
 use crate::map::TilePos;
 
-/// Compute connected networks of placed machines and store them in the `EnergyNetworks` resource.
+/// Keep the `EnergyNetworks` resource up to date as machines are placed and picked up.
 ///
-/// Each network is represented as a `HashSet<TilePos>` containing all placed-machine tile positions
-/// that are 4-connected. This system currently recomputes networks on every tick.
-pub fn compute_energy_networks(
+/// Only the networks around the changed tile are touched, rather than rebuilding everything each
+/// tick. Power poles are connected to any other power pole within both of their
+/// `ConnectionRadius`.
+pub fn update_energy_networks(
+    mut reader: MessageReader<MachinePlacement>,
     mut energy_networks: ResMut<EnergyNetworks>,
     machine_lut: Res<MachineLUT>,
+    power_poles: Query<(&TilePos, &ConnectionRadius), (With<PowerPole>, With<Placed>)>,
+    footprints: Query<(&Footprint, Option<&Direction>), With<Placed>>,
 ) {
-    // Clear previous networks and membership map
-    energy_networks.networks.clear();
-    energy_networks.membership.clear();
+    for placement in reader.read() {
+        match *placement {
+            MachinePlacement::Placed(tile_pos) => {
+                let Some(&machine) = machine_lut.get(&tile_pos) else {
+                    // Picked up again before we got to it
+                    continue;
+                };
 
-    // Visited set for TilePos values
-    let mut visited = HashSet::new();
+                // Wire up power poles which are within range of each other
+                let wired = if let Ok((_, radius)) = power_poles.get(machine) {
+                    power_poles
+                        .iter()
+                        .filter(|(other_pos, other_radius)| {
+                            let range = radius.0.min(other_radius.0);
+                            **other_pos != tile_pos
+                                && tile_pos.0.distance_squared(other_pos.0) as f32 <= range.powi(2)
+                        })
+                        .map(|(other_pos, _)| *other_pos)
+                        .collect()
+                } else {
+                    vec![]
+                };
 
-    // Iterate over all placed machines (keys in the MachineLUT)
-    for pos in machine_lut.0.keys() {
-        if visited.contains(pos) {
-            continue;
-        }
-
-        // Start a new component
-        let mut stack = vec![*pos];
-        visited.insert(*pos);
-        let mut component = HashSet::new();
-
-        while let Some(cur) = stack.pop() {
-            component.insert(cur);
+                // Machines without a direction face the default way
+                let footprint = footprints
+                    .get(machine)
+                    .map(|(footprint, direction)| {
+                        let direction = direction.map_or(IVec2::X, |direction| direction.0);
+                        footprint.tiles(tile_pos, direction).collect()
+                    })
+                    .unwrap_or_else(|_| vec![tile_pos]);
 
-            // Explore adjacent tile positions (4-connected)
-            for neighbour in cur.adjacent() {
-                if machine_lut.0.contains_key(&neighbour) && !visited.contains(&neighbour) {
-                    visited.insert(neighbour);
-                    stack.push(neighbour);
-                }
+                energy_networks.add_machine(tile_pos, footprint, wired);
+            }
+            MachinePlacement::Removed(tile_pos) => {
+                energy_networks.remove_machine(tile_pos);
             }
         }
+    }
+}
 
-        // Record the network and update membership mapping for quick lookup
-        let index = energy_networks.networks.len();
-        for tile in component.iter() {
-            energy_networks.membership.insert(*tile, index);
-        }
-        energy_networks.networks.push(component);
+/// Register the idle power draw of every placed consumer, whether it's working or not
+pub fn request_idle_power(
+    // Broken machines are switched off
+    consumers: Query<(&TilePos, &IdlePowerConsumption), (With<Placed>, Without<Broken>)>,
+    mut energy_networks: ResMut<EnergyNetworks>,
+) {
+    for (tile_pos, idle) in consumers {
+        energy_networks
+            .power_demands
+            .entry(*tile_pos)
+            .or_default()
+            .idle = idle.0;
     }
 }
 
@@ -58,47 +76,137 @@ pub fn produce_energy(
     mut energy_networks: ResMut<EnergyNetworks>,
     energy_producers: Query<(&TilePos, &PowerProduction), With<Placed>>,
 ) {
-    energy_networks.power_available = vec![0.; energy_networks.networks.len()];
+    energy_networks.power_available = energy_networks
+        .networks
+        .keys()
+        .map(|network| (*network, 0.))
+        .collect();
 
     for (tile_pos, production) in energy_producers {
         let network = *energy_networks
             .membership
             .get(tile_pos)
             .expect("Producer has no network");
-        energy_networks.power_available[network] += production.0;
+        *energy_networks
+            .power_available
+            .get_mut(&network)
+            .expect("Network has no power entry") += production.0;
     }
 }
 
-/// Distribute energy in the network according to each machine's needs
-pub fn distribute_energy(mut energy_networks: ResMut<EnergyNetworks>) {
-    energy_networks.power_provided = energy_networks
-        .power_available
-        .iter()
-        .enumerate()
-        // Distribute power for each network
-        .flat_map(|(network, &power)| {
-            // Calculcate network-level power satisfaction
-            let total_demand = energy_networks.networks[network]
-                .iter()
-                .flat_map(|tile_pos| energy_networks.power_demands.get(tile_pos))
-                .sum::<f32>();
-
-            let satisfaction = if total_demand == 0. {
+/// Distribute energy in the network according to each machine's needs.
+///
+/// Any surplus is used to charge batteries in the network, and any shortfall is covered by
+/// discharging them. Batteries share the load in proportion to how much they can take or give
+/// this tick.
+///
+/// Consumers are then supplied in `PowerPriority` order, highest first. Each priority level is
+/// fully satisfied before any power reaches the next level down. Consumers sharing a priority
+/// level are given the same fraction of their demand, so the result doesn't depend on iteration
+/// order.
+pub fn distribute_energy(
+    mut energy_networks: ResMut<EnergyNetworks>,
+    batteries: Query<
+        (&TilePos, &mut StoredEnergy, &BatteryCapacity, &BatteryRate),
+        (With<Battery>, With<Placed>),
+    >,
+    priorities: Machines<&PowerPriority>,
+    timer: Res<Time>,
+) {
+    let dt = timer.delta_secs();
+
+    // Group batteries by network
+    let mut network_batteries = HashMap::<_, Vec<_>>::new();
+    for battery in batteries {
+        let network = *energy_networks
+            .membership
+            .get(battery.0)
+            .expect("Battery has no network");
+        network_batteries.entry(network).or_default().push(battery);
+    }
+
+    let mut power_provided = HashMap::new();
+    let mut battery_flow = HashMap::new();
+    for (network, tiles) in &energy_networks.networks {
+        let power = energy_networks.power_available[network];
+        let batteries = network_batteries.remove(network).unwrap_or_default();
+
+        // Calculate network-level power demand
+        let total_demand = tiles
+            .iter()
+            .flat_map(|tile_pos| energy_networks.power_demands.get(tile_pos))
+            .map(PowerDemand::total)
+            .sum::<f32>();
+
+        // Energy each battery can take in (positive) or give out (negative) this tick
+        let surplus = power - total_demand;
+        let limits = batteries
+            .iter()
+            .map(|(_, stored, capacity, rate)| {
+                if surplus >= 0. {
+                    (rate.0 * dt).min(capacity.0 - stored.0)
+                } else {
+                    -(rate.0 * dt).min(stored.0)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Fraction of each battery's limit that's used
+        let total_limit = limits.iter().sum::<f32>();
+        let usage = if total_limit == 0. {
+            0.
+        } else {
+            (surplus * dt / total_limit).min(1.)
+        };
+
+        // Charge / discharge batteries
+        let mut total_flow = 0.;
+        for ((tile_pos, mut stored, _, _), limit) in batteries.into_iter().zip(limits) {
+            let energy = limit * usage;
+            stored.0 += energy;
+
+            let flow = energy / dt;
+            battery_flow.insert(*tile_pos, flow);
+            total_flow += flow;
+        }
+
+        // Split demands up by priority level
+        let mut priority_demands = HashMap::<_, Vec<_>>::new();
+        for tile_pos in tiles {
+            if let Some(demand) = energy_networks.power_demands.get(tile_pos) {
+                let priority = priorities.get(tile_pos).copied().unwrap_or_default();
+                priority_demands
+                    .entry(priority)
+                    .or_default()
+                    .push((*tile_pos, demand.total()));
+            }
+        }
+
+        // Fill each priority level in turn
+        let mut power_left = (power - total_flow).max(0.);
+        for priority in PowerPriority::ORDER {
+            let Some(demands) = priority_demands.get(&priority) else {
+                continue;
+            };
+
+            // Calculate priority-level power satisfaction
+            let level_demand = demands.iter().map(|(_, demand)| demand).sum::<f32>();
+            let satisfaction = if level_demand == 0. {
                 1.
             } else {
-                (power / total_demand).min(1.)
+                (power_left / level_demand).min(1.)
             };
+            power_left -= level_demand * satisfaction;
 
             // Distribute to each consumer
-            energy_networks.networks[network]
-                .iter()
-                .flat_map(|tile_pos| {
-                    energy_networks
-                        .power_demands
-                        .get(tile_pos)
-                        .map(|demand| (*tile_pos, demand * satisfaction))
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<HashMap<_, _>>();
+            power_provided.extend(
+                demands
+                    .iter()
+                    .map(|(tile_pos, demand)| (*tile_pos, demand * satisfaction)),
+            );
+        }
+    }
+
+    energy_networks.power_provided = power_provided;
+    energy_networks.battery_flow = battery_flow;
 }
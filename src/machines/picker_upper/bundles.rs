@@ -11,15 +11,27 @@ pub struct PickerUpperBundle {
     pickerupper_marker: PickerUpper,
     speed: MachineSpeed,
     power_consumption: PowerConsumption,
+    idle_power_consumption: IdlePowerConsumption,
+    ports: Ports,
+    filter: PickupFilter,
 }
 impl PickerUpperBundle {
-    pub fn new(speed: f32, power_consumption: f32, sprites: Vec<EntitySprite>) -> Self {
+    pub fn new(
+        speed: f32,
+        power_consumption: f32,
+        idle_power_consumption: f32,
+        sprites: Vec<EntitySprite>,
+    ) -> Self {
         Self {
             machine_marker: Machine::PickerUpper,
             animation_sprites: AnimationSprites(sprites),
             pickerupper_marker: PickerUpper,
             speed: MachineSpeed(speed),
             power_consumption: PowerConsumption(power_consumption),
+            idle_power_consumption: IdlePowerConsumption(idle_power_consumption),
+            // Picks up from its own tile and outputs to the front
+            ports: Ports::new([Face::Centre], [Face::Front]),
+            filter: PickupFilter::default(),
         }
     }
 }
@@ -38,7 +50,9 @@ impl PlacedPickerUpperBundle {
     pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
         Self {
             output_direction: Direction(direction),
-            transform: tile_pos.as_transform(Z_RESOURCES),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
             tile_pos,
             placed: Placed,
             targettable: Targettable,
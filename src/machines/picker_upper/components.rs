@@ -1,5 +1,33 @@
 use bevy::prelude::*;
 
+use crate::items::ItemType;
+
 /// Marker for picker-upper machines
 #[derive(Component)]
 pub struct PickerUpper;
+
+/// Distance around a picker-upper that it picks items up from, instead of just its input tiles
+#[derive(Component)]
+pub struct PickupRadius(pub f32);
+
+/// The only type of item a picker-upper will pick up, if set
+#[derive(Component, Default)]
+pub struct PickupFilter(pub Option<ItemType>);
+impl PickupFilter {
+    pub fn allows(&self, item_type: &ItemType) -> bool {
+        self.0.is_none_or(|filter| filter == *item_type)
+    }
+
+    /// Move on to filtering the next raw resource item, going back to no filter after the last
+    /// one. Machines, tools etc. are skipped as they aren't worth automating.
+    pub fn cycle(&mut self) {
+        let mut next = self.0.map_or(0, |item_type| item_type as u8 + 1);
+        self.0 = loop {
+            match ItemType::try_from(next) {
+                Ok(item_type) if item_type.resource_type().is_some() => break Some(item_type),
+                Ok(_) => next += 1,
+                Err(_) => break None,
+            }
+        };
+    }
+}
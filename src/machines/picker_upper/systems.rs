@@ -1,91 +1,151 @@
-use bevy::{platform::collections::HashMap, prelude::*};
+use bevy::{platform::collections::HashSet, prelude::*};
 
 use super::super::components::*;
 use crate::{
-    ground_items::{GroundItem, GroundItemBundle},
+    ground_items::{GroundItem, GroundItemBundle, GroundItemIndex},
     items::ItemType,
-    map::{TilePos, WorldPos},
+    map::TilePos,
+    player::TargettedBy,
 };
 
+/// Find the item a picker-upper should pick up next. Picker-uppers with a radius take the closest
+/// item in range, others the first item on one of their input tiles. Items in `claimed` have
+/// already been picked up this tick.
+fn find_item(
+    machine_pos: &TilePos,
+    direction: &Direction,
+    footprint: &Footprint,
+    ports: &Ports,
+    radius: Option<&PickupRadius>,
+    filter: &PickupFilter,
+    index: &GroundItemIndex,
+    ground_items: &Query<&ItemType, With<GroundItem>>,
+    claimed: &HashSet<Entity>,
+) -> Option<(Entity, ItemType)> {
+    // Items this picker-upper is allowed to pick up, which another one hasn't already taken
+    let allowed = |item: Entity| {
+        ground_items
+            .get(item)
+            .ok()
+            .filter(|_| !claimed.contains(&item))
+            .filter(|item_type| filter.allows(item_type))
+            .map(|item_type| (item, *item_type))
+    };
+
+    if let Some(radius) = radius {
+        index
+            .in_radius(&machine_pos.as_world_pos(), radius.0)
+            .filter_map(|(item, _, distance2)| allowed(item).map(|item| (item, distance2)))
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(item, _)| item)
+    } else {
+        ports
+            .inputs
+            .iter()
+            .map(|face| direction.face_tile(machine_pos, footprint, *face))
+            .find_map(|input_pos| {
+                index
+                    .at_tile(&input_pos)
+                    .find_map(|(item, _)| allowed(item))
+            })
+    }
+}
+
 pub fn precheck_pickeruppers(
-    picker_uppers: Query<(&TilePos, &mut MachineState, &PowerConsumption), With<PickerUpper>>,
-    ground_items: Query<(Entity, &WorldPos, &ItemType), With<GroundItem>>,
+    picker_uppers: Query<
+        (
+            &TilePos,
+            &mut MachineState,
+            &PowerConsumption,
+            &Direction,
+            &Footprint,
+            &Ports,
+            Option<&PickupRadius>,
+            &PickupFilter,
+        ),
+        (With<PickerUpper>, Without<Broken>),
+    >,
+    index: Res<GroundItemIndex>,
+    ground_items: Query<&ItemType, With<GroundItem>>,
     mut energy_networks: ResMut<EnergyNetworks>,
 ) {
-    // Create LUT for ground items
-    // TODO: replace this with some spatial query data structure stored as a resource
-    let ground_items = ground_items.iter().fold(
-        HashMap::<_, Vec<_>>::new(),
-        |mut hm, (entity, world_pos, item_type)| {
-            // NOTE: +0.5 so we search centre of tile instead of origin corner
-            hm.entry((world_pos + Vec2::splat(0.5)).tile())
-                .or_default()
-                .push((entity, item_type));
-
-            hm
-        },
-    );
-
-    for (machine_pos, mut state, power) in picker_uppers {
-        if !ground_items.contains_key(machine_pos) {
+    for (machine_pos, mut state, power, direction, footprint, ports, radius, filter) in
+        picker_uppers
+    {
+        // Check for items in reach
+        if find_item(
+            machine_pos,
+            direction,
+            footprint,
+            ports,
+            radius,
+            filter,
+            &index,
+            &ground_items,
+            &HashSet::new(),
+        )
+        .is_none()
+        {
             // No items, reset progress
             state.0 = 0.;
             continue;
         }
 
-        energy_networks.power_demands.insert(*machine_pos, power.0);
+        energy_networks.request_power(*machine_pos, power.0, 0.);
     }
 }
 
-/// Advance the state of the picker-upper if there's an item on its tile
+/// Advance the state of the picker-upper if there's an item in reach
 pub fn tick_pickeruppers(
     picker_uppers: Query<
         (
             &TilePos,
             &mut MachineState,
             &MachineSpeed,
-            &PowerConsumption,
+            &mut Wear,
             &Direction,
+            &Footprint,
+            &Ports,
+            Option<&PickupRadius>,
+            &PickupFilter,
         ),
         With<PickerUpper>,
     >,
-    machines: Machines<(Entity, &Machine, &AcceptsItems), With<Placed>>,
-    ground_items: Query<(Entity, &WorldPos, &ItemType), With<GroundItem>>,
+    receivers: Receivers,
+    index: Res<GroundItemIndex>,
+    ground_items: Query<&ItemType, With<GroundItem>>,
     energy_networks: Res<EnergyNetworks>,
     timer: Res<Time>,
     mut commands: Commands,
     mut transfer_items: MessageWriter<TransferItem>,
 ) {
-    // Create LUT for ground items
-    // TODO: replace this with some spatial query data structure stored as a resource
-    let ground_items = ground_items.iter().fold(
-        HashMap::<_, Vec<_>>::new(),
-        |mut hm, (entity, world_pos, item_type)| {
-            // NOTE: +0.5 so we search centre of tile instead of origin corner
-            hm.entry((world_pos + Vec2::splat(0.5)).tile())
-                .or_default()
-                .push((entity, item_type));
-
-            hm
-        },
-    );
+    // Ground items aren't removed from the index until commands are applied, so keep track of
+    // what's been picked up by overlapping picker-uppers this tick
+    let mut claimed = HashSet::new();
 
-    for (machine_pos, mut state, speed, power, direction) in picker_uppers {
-        let Some(items) = ground_items.get(machine_pos) else {
+    for (machine_pos, mut state, speed, mut wear, direction, footprint, ports, radius, filter) in
+        picker_uppers
+    {
+        // Pick up the next item in reach
+        let Some((item, item_type)) = find_item(
+            machine_pos,
+            direction,
+            footprint,
+            ports,
+            radius,
+            filter,
+            &index,
+            &ground_items,
+            &claimed,
+        ) else {
             // No items, reset progress
             state.0 = 0.;
             continue;
         };
 
-        // Accumulate energy produced by adjacent machines
-        let energy_supply = energy_networks
-            .power_provided
-            .get(machine_pos)
-            .expect("No power provided for this machine!");
-
-        // Calculate work rate at current power level
-        let satisfaction = (energy_supply / power.0).min(1.0);
-        let work_rate = speed.0 * satisfaction;
+        // Calculate work rate at current power level, slowed down by wear
+        let satisfaction = energy_networks.satisfaction(machine_pos);
+        let work_rate = speed.0 * satisfaction * wear.efficiency();
 
         // Advance state
         state.0 += work_rate * timer.delta_secs();
@@ -94,32 +154,47 @@ pub fn tick_pickeruppers(
             continue;
         }
         state.0 -= 1.0;
+        wear.add_action();
 
-        // Pick up an item
-        let (item, item_type) = *items
-            .first()
-            .expect("If hashmap has entry, there should be at least 1 item");
+        // Pick up the item
+        claimed.insert(item);
         commands.entity(item).remove::<GroundItemBundle>();
 
-        let behind = machine_pos + direction.0;
+        output_item(
+            "Picker-upper",
+            item,
+            &item_type,
+            machine_pos,
+            direction,
+            footprint,
+            ports,
+            &receivers,
+            &mut commands,
+            &mut transfer_items,
+        );
+    }
+}
 
-        // Check if there's something beside it
-        if let Some((machine, machine_type, acceptable_items)) = machines.get(&behind)
-            && acceptable_items.can_accept(item_type)
-        {
-            info!("Transferring item Picker-upper -> {:?}", machine_type);
+/// Cycle the type of item the targetted picker-upper picks up
+pub fn cycle_pickup_filter(
+    targetted_machine: Single<&mut PickupFilter, (With<Placed>, With<TargettedBy>)>,
+) {
+    let mut filter = targetted_machine.into_inner();
+    filter.cycle();
 
-            // Request to transfer to the target machine
-            transfer_items.write(TransferItem {
-                item,
-                target_machine: machine,
-            });
-        } else {
-            info!("Transferring item Picker-upper -> ground");
-            // Drop item on ground
-            commands
-                .entity(item)
-                .insert(GroundItemBundle::new(&behind.as_world_pos()));
-        }
+    info!("Set picker-upper filter to {:?}", filter.0);
+}
+
+/// Outline the area that the targetted picker-upper picks items up from
+pub fn draw_targetted_pickup_radius(
+    picker_uppers: Query<(&TilePos, &PickupRadius), (With<Placed>, With<TargettedBy>)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile_pos, radius) in picker_uppers {
+        gizmos.circle_2d(
+            tile_pos.as_world_pos().0,
+            radius.0,
+            Color::srgba(0.2, 0.8, 0.2, 0.6),
+        );
     }
 }
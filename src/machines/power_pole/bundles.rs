@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+use crate::{consts::Z_RESOURCES, map::TilePos, player::Targettable, sprites::EntitySprite};
+
+/// For power pole machines at all times
+#[derive(Bundle)]
+pub struct PowerPoleBundle {
+    machine_marker: Machine,
+    animation_sprites: AnimationSprites,
+    power_pole_marker: PowerPole,
+    radius: ConnectionRadius,
+}
+impl PowerPoleBundle {
+    pub fn new(radius: f32, sprites: Vec<EntitySprite>) -> Self {
+        Self {
+            machine_marker: Machine::PowerPole,
+            animation_sprites: AnimationSprites(sprites),
+            power_pole_marker: PowerPole,
+            radius: ConnectionRadius(radius),
+        }
+    }
+}
+
+/// For power pole machines when placed down
+#[derive(Bundle)]
+pub struct PlacedPowerPoleBundle {
+    output_direction: Direction,
+    tile_pos: TilePos,
+    transform: Transform,
+    placed: Placed,
+    targettable: Targettable,
+}
+impl PlacedPowerPoleBundle {
+    pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
+        Self {
+            output_direction: Direction(direction),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
+            tile_pos,
+            placed: Placed,
+            targettable: Targettable,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use bevy::prelude::*;
+
+/// Marker for power poles, which link up machines that aren't touching
+#[derive(Component)]
+pub struct PowerPole;
+
+/// Maximum distance in tiles that a power pole can be wired to another pole
+#[derive(Component)]
+pub struct ConnectionRadius(pub f32);
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+
+/// Draw the wires between connected power poles
+pub fn draw_power_wires(energy_networks: Res<EnergyNetworks>, mut gizmos: Gizmos) {
+    // Wires hang off the top of the pole
+    let offset = Vec2::new(0., 0.35);
+
+    for (from, to) in energy_networks.links() {
+        gizmos.line_2d(
+            from.as_world_pos().0 + offset,
+            to.as_world_pos().0 + offset,
+            Color::srgb(0.34, 0.2, 0.18),
+        );
+    }
+}
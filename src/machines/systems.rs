@@ -1,42 +1,87 @@
-use std::f32::consts::FRAC_PI_2;
+use std::{collections::HashSet, f32::consts::FRAC_PI_2};
 
 use bevy::prelude::*;
 
 use super::{bundles::*, components::*};
 pub use super::{
-    harvester::systems::*, network::systems::*, picker_upper::systems::*, transporter::systems::*,
-    windmill::systems::*,
+    burner::systems::*, harvester::systems::*, network::systems::*, picker_upper::systems::*,
+    power_pole::systems::*, transporter::systems::*, underground_belt::systems::*,
+    water_mill::systems::*, windmill::systems::*,
 };
 use crate::{
+    consts::{BELT_STACK_PROGRESS, MACHINE_WEAR_SLOWDOWN, PLAYER_REACH, TILE_RAW_SIZE, Z_GHOST},
     ground_items::GroundItemBundle,
-    items::ItemType,
-    map::{TilePos, WorldPos},
-    player::{HeldBy, HeldItemBundle, Holding, Player, TargettedBy},
-    resources::ResourceNodeLUT,
-    sprites::{GetSprite, SpriteSheets},
+    items::{Freshness, ItemType, Quantity},
+    knowledge::{UnlockName, Unlocked},
+    map::{Chunks, TerrainData, TilePos, WorldPos},
+    player::{CursorPos, HeldBy, HeldItemBundle, Holding, Player, TargettedBy},
+    resources::{ResourceNodeLUT, ResourceType},
+    sprites::{GetSprite, SpriteSheets, TerrainSprite},
     village::{DepositEvent, ResourceStockpile, Stockpiles, VillageCentre},
 };
 
-/// Place a machine at the player's feet
+/// Tile that the player will place a machine on. This is the tile under the mouse cursor, or the
+/// player's feet if the cursor isn't over the window.
+pub fn placement_tile(player_pos: &WorldPos, cursor: &CursorPos) -> TilePos {
+    cursor
+        .tile()
+        .unwrap_or_else(|| (player_pos + Vec2::splat(0.5)).tile())
+}
+
+/// Whether a machine can be placed on a tile: it must be within reach, and every tile it covers
+/// must be free to build on.
+pub fn can_place(
+    tile_pos: &TilePos,
+    footprint: &Footprint,
+    direction: IVec2,
+    player_pos: &WorldPos,
+    machines: &MachineLUT,
+    resources: &ResourceNodeLUT,
+    terrain: &Chunks<&TerrainData>,
+) -> bool {
+    if tile_pos.as_world_pos().0.distance_squared(player_pos.0) > PLAYER_REACH.powi(2) {
+        // Too far away
+        return false;
+    }
+
+    footprint
+        .tiles(*tile_pos, direction)
+        .all(|tile| tile_free(&tile, machines, resources, terrain))
+}
+
+/// Whether a tile can be built on: it must be free of machines & resources, and not water
+fn tile_free(
+    tile_pos: &TilePos,
+    machines: &MachineLUT,
+    resources: &ResourceNodeLUT,
+    terrain: &Chunks<&TerrainData>,
+) -> bool {
+    if machines.0.contains_key(tile_pos) || resources.0.contains_key(tile_pos) {
+        // Something already here
+        return false;
+    }
+
+    let (chunk_pos, offset) = tile_pos.to_chunk_offset();
+    terrain.get(&chunk_pos).is_some_and(|terrain_data| {
+        terrain_data.0[offset.y as usize][offset.x as usize] != TerrainSprite::Water
+    })
+}
+
+/// Place the held machine on the placement tile
 pub fn place_machine(
     player: Single<(&WorldPos, &Holding), With<Player>>,
-    held_machines: Query<(Entity, &Machine), With<HeldBy>>,
+    held_machines: Query<(Entity, &Machine, &Footprint), With<HeldBy>>,
     mut machines: ResMut<MachineLUT>,
     resources: Res<ResourceNodeLUT>,
+    terrain: Chunks<&TerrainData>,
+    direction: Res<PlacementDirection>,
+    cursor: Res<CursorPos>,
     mut commands: Commands,
+    mut placements: MessageWriter<MachinePlacement>,
 ) {
-    let tile_pos = (player.0 + Vec2::splat(0.5)).tile();
-
-    if machines.0.contains_key(&tile_pos) {
-        // Machine already here
-        return;
-    }
-    if resources.0.contains_key(&tile_pos) {
-        // Resource already here
-        return;
-    }
+    let tile_pos = placement_tile(player.0, &cursor);
 
-    let Some((machine, machine_type)) = player
+    let Some((machine, machine_type, footprint)) = player
         .1
         .iter()
         .find_map(|entity| held_machines.get(entity).ok())
@@ -45,9 +90,48 @@ pub fn place_machine(
         return;
     };
 
+    if !can_place(
+        &tile_pos,
+        footprint,
+        direction.0,
+        player.0,
+        &machines,
+        &resources,
+        &terrain,
+    ) {
+        // Blocked
+        return;
+    }
+
+    place_held_machine(
+        &mut commands,
+        &mut machines,
+        &mut placements,
+        machine,
+        machine_type,
+        footprint,
+        tile_pos,
+        direction.0,
+    );
+}
+
+/// Move a held machine onto a tile. Placement validity should be checked beforehand.
+pub fn place_held_machine(
+    commands: &mut Commands,
+    machines: &mut MachineLUT,
+    placements: &mut MessageWriter<MachinePlacement>,
+    machine: Entity,
+    machine_type: &Machine,
+    footprint: &Footprint,
+    tile_pos: TilePos,
+    direction: IVec2,
+) {
     info!("Placing machine {:?} at {:?}", machine_type, tile_pos.0);
 
-    machines.0.insert(tile_pos, machine);
+    for tile in footprint.tiles(tile_pos, direction) {
+        machines.0.insert(tile, machine);
+    }
+    placements.write(MachinePlacement::Placed(tile_pos));
 
     // Place the machine
     commands
@@ -56,14 +140,168 @@ pub fn place_machine(
         .remove::<HeldItemBundle>();
 
     // Add placed machine stuff
-    machine_type.place(&mut commands.entity(machine), tile_pos, IVec2::X);
+    machine_type.place(&mut commands.entity(machine), tile_pos, direction);
+}
+
+/// Show a translucent preview of the held machine where it would be placed, tinted red if it
+/// can't be placed there. Input & output faces are drawn as arrows.
+pub fn update_placement_ghost(
+    player: Single<(&WorldPos, Option<&Holding>), With<Player>>,
+    held_machines: Query<(Entity, &AnimationSprites, &Footprint, Option<&Ports>), With<HeldBy>>,
+    ghost: Option<Single<(Entity, &PlacementGhost, &mut Transform, &mut Sprite)>>,
+    machines: Res<MachineLUT>,
+    resources: Res<ResourceNodeLUT>,
+    terrain: Chunks<&TerrainData>,
+    direction: Res<PlacementDirection>,
+    cursor: Res<CursorPos>,
+    sprite_sheets: Res<SpriteSheets>,
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+) {
+    let (player_pos, holding) = *player;
+
+    let held = holding.and_then(|holding| {
+        holding
+            .iter()
+            .find_map(|entity| held_machines.get(entity).ok())
+    });
+    let Some((machine, sprites, footprint, ports)) = held else {
+        // Nothing to preview
+        if let Some(ghost) = ghost {
+            commands.entity(ghost.0).despawn();
+        }
+        return;
+    };
+
+    let tile_pos = placement_tile(player_pos, &cursor);
+    let valid = can_place(
+        &tile_pos,
+        footprint,
+        direction.0,
+        player_pos,
+        &machines,
+        &resources,
+        &terrain,
+    );
+
+    let transform = tile_pos
+        .as_transform(Z_GHOST)
+        .with_rotation(Quat::from_rotation_z(direction.0.as_vec2().to_angle()))
+        .with_scale(1. / TILE_RAW_SIZE.as_vec2().extend(1.));
+    let colour = if valid {
+        Color::srgba(1., 1., 1., 0.5)
+    } else {
+        Color::srgba(1., 0.2, 0.2, 0.5)
+    };
+
+    match ghost {
+        Some(ghost) if ghost.1.machine == machine => {
+            // Move the existing ghost
+            let (_, _, mut ghost_transform, mut sprite) = ghost.into_inner();
+            *ghost_transform = transform;
+            sprite.color = colour;
+        }
+        ghost => {
+            // Holding a different machine, so replace the ghost
+            if let Some(ghost) = ghost {
+                commands.entity(ghost.0).despawn();
+            }
+
+            let mut sprite = sprites.0[0].get_sprite(&sprite_sheets);
+            sprite.color = colour;
+            commands.spawn((PlacementGhost { machine }, sprite, transform));
+        }
+    }
+
+    // Outline the area covered by bigger machines
+    if *footprint != Footprint::default() {
+        for tile in footprint.tiles(tile_pos, direction.0) {
+            gizmos.rect_2d(tile.as_world_pos().0, Vec2::ONE, colour);
+        }
+    }
+
+    if let Some(ports) = ports {
+        draw_ports(&mut gizmos, &tile_pos, direction.0, footprint, ports);
+    }
+}
+
+/// Draw arrows into a machine's inputs and out of its outputs. Inputs on the machine's own tile
+/// are drawn as a circle.
+fn draw_ports(
+    gizmos: &mut Gizmos,
+    tile_pos: &TilePos,
+    direction: IVec2,
+    footprint: &Footprint,
+    ports: &Ports,
+) {
+    let input_colour = Color::srgb(0.2, 0.8, 0.2);
+    let output_colour = Color::srgb(1., 0.6, 0.1);
+
+    // Tile on the face, and the way out of the machine towards it
+    let face_arrow = |face: &Face| {
+        let face_tile = tile_pos + direction.rotate(footprint.face_offset(*face));
+        (
+            face_tile.as_world_pos().0,
+            direction.rotate(face.offset()).as_vec2(),
+        )
+    };
+
+    for face in &ports.inputs {
+        if *face == Face::Centre {
+            gizmos.circle_2d(tile_pos.as_world_pos().0, 0.25, input_colour);
+            continue;
+        }
+
+        let (face_pos, outwards) = face_arrow(face);
+        gizmos.arrow_2d(face_pos, face_pos - outwards * 0.5, input_colour);
+    }
+    for face in &ports.outputs {
+        let (face_pos, outwards) = face_arrow(face);
+        gizmos.arrow_2d(face_pos - outwards * 0.5, face_pos, output_colour);
+    }
+}
+
+/// Show the ports of the targetted machine
+pub fn draw_targetted_ports(
+    machines: Query<(&TilePos, &Direction, &Footprint, &Ports), (With<Placed>, With<TargettedBy>)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile_pos, direction, footprint, ports) in machines {
+        draw_ports(&mut gizmos, tile_pos, direction.0, footprint, ports);
+    }
+}
+
+/// Move the targetted machine's output round to the next free side
+pub fn cycle_output_face(
+    targetted_machine: Single<(&Machine, &mut Ports), (With<Placed>, With<TargettedBy>)>,
+) {
+    let (machine_type, mut ports) = targetted_machine.into_inner();
+    ports.rotate_output();
+
+    info!("Set {:?} outputs to {:?}", machine_type, ports.outputs);
+}
+
+/// Rotate the held machine's placement clockwise
+pub fn rotate_placement(mut direction: ResMut<PlacementDirection>) {
+    // 90 degree turn clockwise
+    let right_turn = IVec2::new(0, -1);
+
+    direction.0 = right_turn.rotate(direction.0);
+    info!("Rotating placement to {:?}", direction.0);
 }
 
 /// Pickup a machine, dropping and contained items on the ground
 pub fn pickup_machine(
     player: Single<Entity, With<Player>>,
     targetted_machine: Single<
-        (Entity, &Machine, Option<&Transporting>, &TilePos),
+        (
+            Entity,
+            &Machine,
+            Option<&Transporting>,
+            &TilePos,
+            &Footprint,
+            &Direction,
+        ),
         (
             With<Placed>,
             With<Machine>,
@@ -73,8 +311,9 @@ pub fn pickup_machine(
     >,
     mut machine_lut: ResMut<MachineLUT>,
     mut commands: Commands,
+    mut placements: MessageWriter<MachinePlacement>,
 ) {
-    let (machine, machine_type, items, pos) = *targetted_machine;
+    let (machine, machine_type, items, pos, footprint, direction) = *targetted_machine;
     info!("Picking up {:?} at {:?}", machine_type, pos.0);
 
     // Drop items out of machine
@@ -94,8 +333,11 @@ pub fn pickup_machine(
         .entity(machine)
         .insert(HeldItemBundle::new(*player));
 
-    // Remove LUT entry for the machine
-    machine_lut.0.remove(pos);
+    // Remove LUT entries for the machine
+    for tile in footprint.tiles(*pos, direction.0) {
+        machine_lut.0.remove(&tile);
+    }
+    placements.write(MachinePlacement::Removed(*pos));
 }
 
 /// Cycle through the sprites as the machine makes progress
@@ -125,41 +367,342 @@ pub fn animate_machine(
 
 /// Rotate a machine clockwise
 pub fn rotate_machine(
-    mut targetted_machine: Single<
-        (&mut Direction, &mut Transform),
+    targetted_machine: Single<
+        (Entity, &mut Direction, &mut Transform, &TilePos, &Footprint),
         (With<Placed>, With<Machine>, With<TargettedBy>),
     >,
+    mut machines: ResMut<MachineLUT>,
+    resources: Res<ResourceNodeLUT>,
+    terrain: Chunks<&TerrainData>,
+    mut placements: MessageWriter<MachinePlacement>,
 ) {
-    info!("Rotating machine");
+    let (machine, mut direction, mut transform, tile_pos, footprint) =
+        targetted_machine.into_inner();
 
     // 90 degree turn clockwise
     let right_turn = IVec2::new(0, -1);
+    let new_direction = right_turn.rotate(direction.0);
+
+    if *footprint != Footprint::default() {
+        // Bigger machines swing round their corner, so need room to turn
+        let old_tiles = footprint.tiles(*tile_pos, direction.0).collect::<Vec<_>>();
+        if !footprint.tiles(*tile_pos, new_direction).all(|tile| {
+            old_tiles.contains(&tile) || tile_free(&tile, &machines, &resources, &terrain)
+        }) {
+            info!("No room to rotate machine");
+            return;
+        }
+
+        for tile in &old_tiles {
+            machines.0.remove(tile);
+        }
+        for tile in footprint.tiles(*tile_pos, new_direction) {
+            machines.0.insert(tile, machine);
+        }
+
+        // Reconnect with whatever it's touching now
+        placements.write(MachinePlacement::Removed(*tile_pos));
+        placements.write(MachinePlacement::Placed(*tile_pos));
+    }
+
+    info!("Rotating machine");
+    direction.0 = new_direction;
+    transform.rotate_z(-FRAC_PI_2);
+}
 
-    targetted_machine.0.0 = right_turn.rotate(targetted_machine.0.0);
-    targetted_machine.1.rotate_z(-FRAC_PI_2);
+/// Cycle the power priority of a machine
+pub fn cycle_power_priority(
+    targetted_machine: Single<(&Machine, &mut PowerPriority), (With<Placed>, With<TargettedBy>)>,
+) {
+    let (machine_type, mut priority) = targetted_machine.into_inner();
+    *priority = priority.next();
+
+    info!("Set {:?} power priority to {:?}", machine_type, *priority);
+}
+
+/// Take resources from the village stockpiles, if there's enough of all of them
+fn pay_resources(
+    stockpiles: &mut Stockpiles<&mut ResourceStockpile, (Without<ItemType>, Without<Machine>)>,
+    cost: &[(ResourceType, usize)],
+) -> bool {
+    // Check if we've got enough resources
+    if !cost.iter().all(|(res_type, amount)| {
+        stockpiles
+            .get(res_type)
+            .is_some_and(|stockpile| stockpile.0 >= *amount as f32)
+    }) {
+        return false;
+    }
+
+    // Remove resources
+    for (res_type, amount) in cost {
+        let mut stockpile = stockpiles
+            .get_mut(res_type)
+            .expect("Already checked they exist above");
+
+        stockpile.0 -= *amount as f32;
+    }
+
+    true
+}
+
+/// Upgrade the targetted machine to the next tier, paying for it from the village stockpiles. The
+/// machine stays where it is, so it keeps its direction and anything it's working on.
+///
+/// Consumers work faster, generators produce more, batteries hold more and power poles reach
+/// further. Windmills read their tier when working out their production.
+pub fn upgrade_machine(
+    targetted_machine: Single<
+        (
+            &Machine,
+            &TilePos,
+            &mut MachineTier,
+            Option<&mut MachineSpeed>,
+            Option<&mut PowerConsumption>,
+            Option<&mut IdlePowerConsumption>,
+            Option<&mut LoadPowerConsumption>,
+            Option<&mut BurnerOutput>,
+            Option<&mut FlowPower>,
+            Option<(&mut BatteryCapacity, &mut BatteryRate)>,
+            Option<&mut ConnectionRadius>,
+        ),
+        (With<Placed>, With<TargettedBy>),
+    >,
+    knowledge: Query<&UnlockName, With<Unlocked>>,
+    mut stockpiles: Stockpiles<&mut ResourceStockpile, (Without<ItemType>, Without<Machine>)>,
+    mut commands: Commands,
+    mut placements: MessageWriter<MachinePlacement>,
+) {
+    let (
+        machine_type,
+        tile_pos,
+        mut tier,
+        speed,
+        power,
+        idle_power,
+        load_power,
+        burner_output,
+        flow_power,
+        battery,
+        connection_radius,
+    ) = targetted_machine.into_inner();
+
+    if speed.is_none() && battery.is_none() && connection_radius.is_none() {
+        // Eg. the village centre
+        commands.trigger(FailedUpgrade {
+            machine: format!("{machine_type:?}"),
+            reason: FailedUpgradeReason::NotUpgradeable,
+        });
+        return;
+    }
+
+    let Some(next_tier) = tier.next() else {
+        commands.trigger(FailedUpgrade {
+            machine: format!("{machine_type:?}"),
+            reason: FailedUpgradeReason::MaxTier,
+        });
+        return;
+    };
+
+    // Check the tier has been unlocked
+    if let Some(required) = next_tier.knowledge()
+        && !knowledge.iter().any(|name| name.0 == required)
+    {
+        commands.trigger(FailedUpgrade {
+            machine: format!("{machine_type:?}"),
+            reason: FailedUpgradeReason::NotUnlocked,
+        });
+        return;
+    }
+
+    if !pay_resources(&mut stockpiles, &next_tier.upgrade_cost()) {
+        commands.trigger(FailedUpgrade {
+            machine: format!("{machine_type:?}"),
+            reason: FailedUpgradeReason::NotEnoughResources,
+        });
+        return;
+    }
+
+    // Re-scale stats from the old tier to the new one
+    let speed_scale = next_tier.speed_multiplier() / tier.speed_multiplier();
+    let power_scale = next_tier.power_multiplier() / tier.power_multiplier();
+    if let Some(mut speed) = speed {
+        speed.0 *= speed_scale;
+    }
+    if let Some(mut power) = power {
+        power.0 *= power_scale;
+    }
+    if let Some(mut idle_power) = idle_power {
+        idle_power.0 *= power_scale;
+    }
+    if let Some(mut load_power) = load_power {
+        load_power.0 *= power_scale;
+    }
+
+    // Generators & storage get better at the same rate consumers speed up
+    if let Some(mut burner_output) = burner_output {
+        burner_output.0 *= speed_scale;
+    }
+    if let Some(mut flow_power) = flow_power {
+        flow_power.0 *= speed_scale;
+    }
+    if let Some((mut capacity, mut rate)) = battery {
+        capacity.0 *= speed_scale;
+        rate.0 *= speed_scale;
+    }
+    if let Some(mut connection_radius) = connection_radius {
+        connection_radius.0 *= speed_scale;
+
+        // Re-wire with any poles that are now in range
+        placements.write(MachinePlacement::Removed(*tile_pos));
+        placements.write(MachinePlacement::Placed(*tile_pos));
+    }
+
+    *tier = next_tier;
+    info!("Upgraded {:?} to {:?}", machine_type, *tier);
+}
+
+/// Mark machines which have worn out as broken
+pub fn break_worn_machines(
+    machines: Query<(Entity, &Machine, &Wear), (Changed<Wear>, Without<Broken>)>,
+    mut commands: Commands,
+) {
+    for (entity, machine_type, wear) in machines {
+        if !wear.is_broken() {
+            continue;
+        }
+
+        info!("{:?} broke down", machine_type);
+        commands.entity(entity).insert(Broken);
+        commands.trigger(MachineBrokeDown {
+            machine: format!("{machine_type:?}"),
+        });
+    }
+}
+
+/// Repair the targetted machine back to new, paying for it from the village stockpiles
+pub fn repair_machine(
+    targetted_machine: Single<(Entity, &Machine, &mut Wear), (With<Placed>, With<TargettedBy>)>,
+    mut stockpiles: Stockpiles<&mut ResourceStockpile, (Without<ItemType>, Without<Machine>)>,
+    mut commands: Commands,
+) {
+    let (entity, machine_type, mut wear) = targetted_machine.into_inner();
+
+    if wear.0 == 0. {
+        commands.trigger(FailedRepair {
+            machine: format!("{machine_type:?}"),
+            reason: FailedRepairReason::NotWorn,
+        });
+        return;
+    }
+
+    if !pay_resources(&mut stockpiles, &wear.repair_cost()) {
+        commands.trigger(FailedRepair {
+            machine: format!("{machine_type:?}"),
+            reason: FailedRepairReason::NotEnoughResources,
+        });
+        return;
+    }
+
+    wear.0 = 0.;
+    commands.entity(entity).remove::<Broken>();
+    info!("Repaired {:?}", machine_type);
+}
+
+/// Cross out broken machines, and show how much life is left in worn ones
+pub fn draw_machine_wear(
+    machines: Query<(&TilePos, &Wear), (With<Placed>, With<Machine>)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile_pos, wear) in machines {
+        let centre = tile_pos.as_world_pos().0;
+
+        if wear.is_broken() {
+            let colour = Color::srgb(0.9, 0.1, 0.1);
+            gizmos.line_2d(centre - 0.4, centre + 0.4, colour);
+            gizmos.line_2d(
+                centre + Vec2::new(-0.4, 0.4),
+                centre + Vec2::new(0.4, -0.4),
+                colour,
+            );
+        } else if wear.0 > MACHINE_WEAR_SLOWDOWN {
+            // Bar along the bottom edge which shrinks as the machine wears out
+            let start = centre + Vec2::new(-0.4, -0.4);
+            let length = 0.8 * (1. - wear.0);
+            gizmos.line_2d(start, start + Vec2::X * length, Color::srgb(1., 0.6, 0.1));
+        }
+    }
+}
+
+/// Mark upgraded machines with a pip for each tier above the first
+pub fn draw_machine_tiers(
+    machines: Query<(&TilePos, &MachineTier), (With<Placed>, With<Machine>)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile_pos, tier) in machines {
+        let pips = match tier {
+            MachineTier::Wooden => 0,
+            MachineTier::Stone => 1,
+            MachineTier::Iron => 2,
+        };
+
+        let corner = tile_pos.as_world_pos().0 + Vec2::new(-0.35, 0.35);
+        for i in 0..pips {
+            gizmos.circle_2d(
+                corner + Vec2::X * 0.15 * i as f32,
+                0.05,
+                Color::srgb(0.8, 0.8, 0.85),
+            );
+        }
+    }
+}
+
+/// Outline the tiles covered by machines bigger than one tile, as their sprite only covers the
+/// first
+pub fn draw_machine_footprints(
+    machines: Query<(&TilePos, &Direction, &Footprint), (With<Placed>, With<Machine>)>,
+    mut gizmos: Gizmos,
+) {
+    for (tile_pos, direction, footprint) in machines {
+        if *footprint == Footprint::default() {
+            continue;
+        }
+
+        for tile in footprint.tiles(*tile_pos, direction.0) {
+            gizmos.rect_2d(tile.as_world_pos().0, Vec2::ONE, Color::srgb(0.6, 0.6, 0.6));
+        }
+    }
 }
 
 /// Transfer items from the ether into machines
 pub fn transfer_items(
     mut reader: MessageReader<TransferItem>,
-    machines: Query<(EntityRef, &Machine, &AcceptsItems), With<Placed>>,
-    items: Query<&ItemType>,
+    machines: Query<(&Machine, &AcceptsItems, &TilePos, Option<&Direction>), With<Placed>>,
+    mut fuel_buffers: Query<(&mut FuelBuffer, &FuelCapacity)>,
+    mut items: Query<(&ItemType, &mut Quantity)>,
+    mut freshness: Query<&mut Freshness>,
+    belts: Query<&Children>,
+    transported: Query<(&TransportedBy, &MachineState)>,
     mut stockpiles: Stockpiles<&mut ResourceStockpile, (Without<ItemType>, Without<Machine>)>,
     mut commands: Commands,
 ) {
+    // Items moved off their belts by earlier transfers, which can't be stacked onto any more
+    let mut handled = HashSet::new();
     for TransferItem {
         item,
         target_machine,
     } in reader.read()
     {
+        handled.insert(*item);
+
         // Get target machine
-        let (machine, machine_type, accceptable_items) = machines
+        let (machine_type, accceptable_items, tile_pos, direction) = machines
             .get(*target_machine)
             .expect("Target machine does not exist!");
 
         // Verify that this transfer can happen (this should already be checked before the transfer
         // request, so this is a sanity check)
-        let item_type = items.get(*item).expect("Item doesn't exist!");
+        let (item_type, mut quantity) = items.get_mut(*item).expect("Item doesn't exist!");
         assert!(
             accceptable_items.can_accept(item_type),
             "Machine cannot accept this item"
@@ -167,14 +710,53 @@ pub fn transfer_items(
 
         use Machine::*;
         match machine_type {
-            Transporter => {
-                let direction = machine
-                    .get::<Direction>()
-                    .expect("Machine does not have a direction!");
+            Transporter | UndergroundBelt => {
+                let direction = direction.expect("Machine does not have a direction!");
+                let item_type = *item_type;
+
+                // Stack onto the same item if it's just been put on the belt, so a steady trickle
+                // of single items is carried as a few stacks
+                let on_belt = belts
+                    .get(*target_machine)
+                    .map(|children| {
+                        children
+                            .iter()
+                            .filter(|stack| !handled.contains(stack))
+                            .filter(|stack| {
+                                transported
+                                    .get(*stack)
+                                    .is_ok_and(|(transported_by, progress)| {
+                                        transported_by.0 == *target_machine
+                                            && progress.0 < BELT_STACK_PROGRESS
+                                    })
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                for stack in on_belt {
+                    if let Ok([(stack_type, mut stack_quantity), (_, mut quantity)]) =
+                        items.get_many_mut([stack, *item])
+                        && *stack_type == item_type
+                    {
+                        let moved =
+                            stack_quantity.take_from(&mut quantity, item_type.max_stack_size());
+                        Freshness::merge_stacks(
+                            &mut freshness,
+                            stack,
+                            *item,
+                            stack_quantity.0 - moved,
+                            moved,
+                        );
+                    }
+                }
+                if items.get(*item).is_ok_and(|(_, quantity)| quantity.0 == 0) {
+                    commands.entity(*item).despawn();
+                    continue;
+                }
 
                 commands
                     .entity(*item)
-                    .insert(TransportedItemBundle::new(machine.id(), direction));
+                    .insert(TransportedItemBundle::new(*target_machine, direction));
             }
             VillageCentre => {
                 let resource = item_type
@@ -186,7 +768,7 @@ pub fn transfer_items(
                     .expect("Stockpile not created!");
 
                 // TODO: Different items giving different amounts of a resource
-                let amount = 1;
+                let amount = quantity.0;
                 stockpile.0 += amount as f32;
 
                 // Remove the item
@@ -194,6 +776,31 @@ pub fn transfer_items(
 
                 commands.trigger(DepositEvent { resource, amount });
             }
+            Burner => {
+                let burn_time = item_type.burn_time().expect("Item is not a fuel!");
+
+                let (mut fuel, capacity) = fuel_buffers
+                    .get_mut(*target_machine)
+                    .expect("Burner has no fuel buffer!");
+
+                // Only take as much of the stack as there's room for. The burner was accepting
+                // fuel, so at least one always goes in.
+                let room = ((capacity.0 - fuel.0) / burn_time).floor().max(1.) as usize;
+                let burnt = quantity.0.min(room);
+                fuel.0 += burn_time * burnt as f32;
+
+                if burnt == quantity.0 {
+                    // Fuel is used up
+                    commands.entity(*item).despawn();
+                } else {
+                    // Leftovers are dropped beside the burner
+                    quantity.0 -= burnt;
+                    commands
+                        .entity(*item)
+                        .remove::<TransportedItemBundle>()
+                        .insert(GroundItemBundle::new(&tile_pos.as_world_pos()));
+                }
+            }
             _ => unreachable!("Machine accepts items but logic not here!"),
         };
     }
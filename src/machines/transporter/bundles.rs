@@ -16,17 +16,30 @@ pub struct TransporterBundle {
     transporter_marker: Transporter,
     speed: MachineSpeed,
     power_consumption: PowerConsumption,
+    idle_power_consumption: IdlePowerConsumption,
+    load_power_consumption: LoadPowerConsumption,
     accepts_items: AcceptsItems,
+    ports: Ports,
 }
 impl TransporterBundle {
-    pub fn new(speed: f32, power_consumption: f32, sprites: Vec<EntitySprite>) -> Self {
+    pub fn new(
+        speed: f32,
+        power_consumption: f32,
+        idle_power_consumption: f32,
+        load_power_consumption: f32,
+        sprites: Vec<EntitySprite>,
+    ) -> Self {
         Self {
             machine_marker: Machine::Transporter,
             transporter_marker: Transporter,
             speed: MachineSpeed(speed),
             power_consumption: PowerConsumption(power_consumption),
+            idle_power_consumption: IdlePowerConsumption(idle_power_consumption),
+            load_power_consumption: LoadPowerConsumption(load_power_consumption),
             animation_sprites: AnimationSprites(sprites),
             accepts_items: AcceptsItems::Any,
+            // Items can join from behind or the sides
+            ports: Ports::new([Face::Back, Face::Left, Face::Right], [Face::Front]),
         }
     }
 }
@@ -44,7 +57,9 @@ impl PlacedTransporterBundle {
     pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
         Self {
             output_direction: Direction(direction),
-            transform: tile_pos.as_transform(Z_RESOURCES),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
             tile_pos,
             targettable: Targettable,
             placed: Placed,
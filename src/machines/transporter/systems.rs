@@ -1,20 +1,40 @@
 use bevy::prelude::*;
 
 use super::{super::components::*, bundles::*};
-use crate::{ground_items::GroundItemBundle, items::ItemType, map::TilePos};
+use crate::{
+    ground_items::GroundItemBundle,
+    items::{ItemType, Quantity},
+    map::TilePos,
+};
 
 pub fn precheck_transporters(
-    transported_items: Query<(), With<TransportedBy>>,
-    transporters: Query<(&PowerConsumption, &Children, &TilePos), With<Transporter>>,
+    transported_items: Query<&Quantity, With<TransportedBy>>,
+    transporters: Query<
+        (
+            &PowerConsumption,
+            &LoadPowerConsumption,
+            &Children,
+            &TilePos,
+        ),
+        // Underground belts use power the same way
+        (
+            Or<(With<Transporter>, With<UndergroundBelt>)>,
+            Without<Broken>,
+        ),
+    >,
     mut energy_networks: ResMut<EnergyNetworks>,
 ) {
-    for (power, children, machine_pos) in transporters {
-        if children
+    for (power, load_power, children, machine_pos) in transporters {
+        let num_items = children
             .iter()
-            .any(|child| transported_items.contains(child))
-        {
-            // TODO: Increase power consumption per item?
-            energy_networks.power_demands.insert(*machine_pos, power.0);
+            .filter_map(|child| transported_items.get(child).ok())
+            .map(|quantity| quantity.0)
+            .sum::<usize>();
+
+        if num_items > 0 {
+            // Heavier belts need more power to move
+            let load = num_items as f32 * load_power.0;
+            energy_networks.request_power(*machine_pos, power.0, load);
         }
     }
 }
@@ -28,30 +48,25 @@ pub fn tick_transporters(
     transporters: Query<
         (
             &MachineSpeed,
-            &PowerConsumption,
+            &mut Wear,
             &Direction,
+            &Footprint,
+            &Ports,
             &Children,
             &TilePos,
         ),
         With<Transporter>,
     >,
-    machines: Machines<(Entity, &Machine, &AcceptsItems), With<Placed>>,
+    receivers: Receivers,
     energy_networks: Res<EnergyNetworks>,
     timer: Res<Time>,
     mut commands: Commands,
     mut transfer_items: MessageWriter<TransferItem>,
 ) {
-    for (speed, power, direction, children, machine_pos) in transporters {
-        // Accumulate energy produced by adjacent machines (e.g., windmills)
-        let energy_supply = energy_networks
-            .power_provided
-            .get(machine_pos)
-            // Default to 0 here instead of panicing as pre-checking children is expensive
-            .unwrap_or(&0.);
-
-        // Calculate work rate based on current power supply
-        let satisfaction = (energy_supply / power.0).min(1.0);
-        let work_rate = speed.0 * satisfaction;
+    for (speed, mut wear, direction, footprint, ports, children, machine_pos) in transporters {
+        // Calculate work rate based on current power supply, slowed down by wear
+        let satisfaction = energy_networks.satisfaction(machine_pos);
+        let work_rate = speed.0 * satisfaction * wear.efficiency();
 
         for child in children {
             let Ok((item, mut transform, mut progress, item_type)) =
@@ -72,12 +87,17 @@ pub fn tick_transporters(
                 // Still on the belt
                 continue;
             }
+            wear.add_action();
 
             // Move item off the conveyor
-            let adjacent_pos = machine_pos + direction.0;
-            if let Some((machine, machine_type, acceptable_items)) = machines.get(&adjacent_pos)
-                && acceptable_items.can_accept(item_type)
-            {
+            let output_tiles = ports
+                .outputs
+                .iter()
+                .map(|face| direction.face_tile(machine_pos, footprint, *face))
+                .collect::<Vec<_>>();
+            if let Some((machine, machine_type)) = output_tiles.iter().find_map(|output_pos| {
+                find_receiver(&receivers, output_pos, machine_pos, item_type)
+            }) {
                 info!("Transferring item Transporter -> {:?}", machine_type);
 
                 // Request to transfer to the target machine
@@ -89,9 +109,10 @@ pub fn tick_transporters(
                 // Drop item on ground
                 commands.entity(item).remove::<TransportedItemBundle>();
 
+                let output_pos = output_tiles.first().unwrap_or(machine_pos);
                 commands
                     .entity(item)
-                    .insert(GroundItemBundle::new(&adjacent_pos.as_world_pos()));
+                    .insert(GroundItemBundle::new(&output_pos.as_world_pos()));
             }
         }
     }
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+use crate::{consts::Z_RESOURCES, map::TilePos, player::Targettable, sprites::EntitySprite};
+
+/// For underground belt machines at all times
+#[derive(Bundle)]
+pub struct UndergroundBeltBundle {
+    machine_marker: Machine,
+    animation_sprites: AnimationSprites,
+    underground_marker: UndergroundBelt,
+    end: UndergroundEnd,
+    range: UndergroundRange,
+    speed: MachineSpeed,
+    power_consumption: PowerConsumption,
+    idle_power_consumption: IdlePowerConsumption,
+    load_power_consumption: LoadPowerConsumption,
+    accepts_items: AcceptsItems,
+    ports: Ports,
+}
+impl UndergroundBeltBundle {
+    pub fn new(
+        end: UndergroundEnd,
+        range: i32,
+        speed: f32,
+        power_consumption: f32,
+        idle_power_consumption: f32,
+        load_power_consumption: f32,
+        sprites: Vec<EntitySprite>,
+    ) -> Self {
+        let ports = match end {
+            // Items go in like a transporter, but come out at the exit
+            UndergroundEnd::Entrance => Ports::new([Face::Back, Face::Left, Face::Right], []),
+            // Items only come in from the entrance
+            UndergroundEnd::Exit => Ports::new([], [Face::Front]),
+        };
+
+        Self {
+            machine_marker: Machine::UndergroundBelt,
+            animation_sprites: AnimationSprites(sprites),
+            underground_marker: UndergroundBelt,
+            end,
+            range: UndergroundRange(range),
+            speed: MachineSpeed(speed),
+            power_consumption: PowerConsumption(power_consumption),
+            idle_power_consumption: IdlePowerConsumption(idle_power_consumption),
+            load_power_consumption: LoadPowerConsumption(load_power_consumption),
+            accepts_items: AcceptsItems::Any,
+            ports,
+        }
+    }
+}
+
+/// For underground belt machines when placed down
+#[derive(Bundle)]
+pub struct PlacedUndergroundBeltBundle {
+    output_direction: Direction,
+    tile_pos: TilePos,
+    transform: Transform,
+    placed: Placed,
+    targettable: Targettable,
+}
+impl PlacedUndergroundBeltBundle {
+    pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
+        Self {
+            output_direction: Direction(direction),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
+            tile_pos,
+            targettable: Targettable,
+            placed: Placed,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+/// Marker for belts which carry items underground to a matching exit
+#[derive(Component)]
+pub struct UndergroundBelt;
+
+/// Which end of an underground belt pair this is
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UndergroundEnd {
+    /// Takes items in and sends them underground
+    Entrance,
+    /// Brings items back up and outputs them
+    Exit,
+}
+
+/// Furthest an entrance can be from its exit, in tiles
+#[derive(Component)]
+pub struct UndergroundRange(pub i32);
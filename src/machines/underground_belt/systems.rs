@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use super::super::{bundles::TransportedItemBundle, components::*};
+use crate::{ground_items::GroundItemBundle, items::ItemType, map::TilePos};
+
+/// Find the exit that an underground belt entrance sends its items to. This is the first
+/// underground belt ahead of it within range which faces the same way, as long as it's an exit.
+/// Belts facing other ways are passed under, as are any other tiles in between (including water).
+fn find_exit(
+    tile_pos: &TilePos,
+    direction: &Direction,
+    range: &UndergroundRange,
+    ends: &Machines<(Entity, &UndergroundEnd, &Direction), (With<UndergroundBelt>, With<Placed>)>,
+) -> Option<Entity> {
+    (1..=range.0)
+        .map(|distance| tile_pos + direction.0 * distance)
+        .filter_map(|pos| ends.get(&pos))
+        .find(|(_, _, other_direction)| other_direction.0 == direction.0)
+        .and_then(|(exit, end, _)| (*end == UndergroundEnd::Exit).then_some(exit))
+}
+
+/// Move items along underground belts. Items reaching the end of an entrance are sent to its
+/// exit, and items reaching the end of an exit are output like a transporter.
+pub fn tick_underground_belts(
+    mut transported_items: Query<
+        (Entity, &mut Transform, &mut MachineState, &ItemType),
+        With<TransportedBy>,
+    >,
+    belts: Query<
+        (
+            &MachineSpeed,
+            &mut Wear,
+            &Direction,
+            &Footprint,
+            &Ports,
+            &UndergroundEnd,
+            &UndergroundRange,
+            &Children,
+            &TilePos,
+        ),
+        With<UndergroundBelt>,
+    >,
+    ends: Machines<(Entity, &UndergroundEnd, &Direction), (With<UndergroundBelt>, With<Placed>)>,
+    receivers: Receivers,
+    energy_networks: Res<EnergyNetworks>,
+    timer: Res<Time>,
+    mut commands: Commands,
+    mut transfer_items: MessageWriter<TransferItem>,
+) {
+    for (speed, mut wear, direction, footprint, ports, end, range, children, machine_pos) in belts {
+        // Calculate work rate based on current power supply, slowed down by wear
+        let satisfaction = energy_networks.satisfaction(machine_pos);
+        let work_rate = speed.0 * satisfaction * wear.efficiency();
+
+        for child in children {
+            let Ok((item, mut transform, mut progress, item_type)) =
+                transported_items.get_mut(*child)
+            else {
+                // Non-item child
+                continue;
+            };
+
+            // Move item along the belt, the same as transporters
+            progress.0 += work_rate * timer.delta_secs();
+            transform.translation =
+                Vec3::new(progress.0.min(1.) - 0.5, 0., transform.translation.z);
+
+            // Check if the item has reached the end
+            if progress.0 < 1. {
+                continue;
+            }
+
+            match end {
+                UndergroundEnd::Entrance => {
+                    let Some(exit) = find_exit(machine_pos, direction, range, &ends) else {
+                        // No exit, so wait at the end of the belt
+                        progress.0 = 1.;
+                        continue;
+                    };
+
+                    info!("Transferring item underground");
+                    wear.add_action();
+                    transfer_items.write(TransferItem {
+                        item,
+                        target_machine: exit,
+                    });
+                }
+                UndergroundEnd::Exit => {
+                    wear.add_action();
+                    let output_tiles = ports
+                        .outputs
+                        .iter()
+                        .map(|face| direction.face_tile(machine_pos, footprint, *face))
+                        .collect::<Vec<_>>();
+                    if let Some((machine, machine_type)) =
+                        output_tiles.iter().find_map(|output_pos| {
+                            find_receiver(&receivers, output_pos, machine_pos, item_type)
+                        })
+                    {
+                        info!("Transferring item Underground belt -> {:?}", machine_type);
+
+                        // Request to transfer to the target machine
+                        transfer_items.write(TransferItem {
+                            item,
+                            target_machine: machine,
+                        });
+                    } else {
+                        // Drop item on ground
+                        commands.entity(item).remove::<TransportedItemBundle>();
+
+                        let output_pos = output_tiles.first().unwrap_or(machine_pos);
+                        commands
+                            .entity(item)
+                            .insert(GroundItemBundle::new(&output_pos.as_world_pos()));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use super::super::components::*;
+use crate::{consts::Z_RESOURCES, map::TilePos, player::Targettable, sprites::EntitySprite};
+
+/// For water mill machines at all times (stateless parts of the prefab)
+#[derive(Bundle)]
+pub struct WaterMillBundle {
+    machine_marker: Machine,
+    animation_sprites: AnimationSprites,
+    water_mill_marker: WaterMill,
+    flow_power: FlowPower,
+    // Speed is just used for animation for water mills
+    speed: MachineSpeed,
+}
+impl WaterMillBundle {
+    pub fn new(flow_power: f32, speed: f32, sprites: Vec<EntitySprite>) -> Self {
+        Self {
+            machine_marker: Machine::WaterMill,
+            animation_sprites: AnimationSprites(sprites),
+            water_mill_marker: WaterMill,
+            flow_power: FlowPower(flow_power),
+            speed: MachineSpeed(speed),
+        }
+    }
+}
+
+/// For water mill machines when placed down (stateful / instance-specific components)
+#[derive(Bundle)]
+pub struct PlacedWaterMillBundle {
+    output_direction: Direction,
+    tile_pos: TilePos,
+    transform: Transform,
+    placed: Placed,
+    targettable: Targettable,
+    current_production: PowerProduction,
+    // State is just used for animation for water mills
+    state: MachineState,
+}
+impl PlacedWaterMillBundle {
+    pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
+        Self {
+            output_direction: Direction(direction),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
+            tile_pos,
+            placed: Placed,
+            targettable: Targettable,
+            current_production: PowerProduction(0.0),
+            state: MachineState(0.),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use bevy::prelude::*;
+
+use super::super::components::Wear;
+
+/// Marker for water mills, which generate power from flowing water
+#[derive(Component)]
+#[require(Wear)]
+pub struct WaterMill;
+
+/// Power produced per unit of water flow
+#[derive(Component)]
+pub struct FlowPower(pub f32);
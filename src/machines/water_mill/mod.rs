@@ -0,0 +1,3 @@
+pub mod bundles;
+pub mod components;
+pub mod systems;
@@ -0,0 +1,84 @@
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use super::super::components::*;
+use crate::{
+    consts::WATER_MILL_FLOW_RADIUS,
+    map::{Chunks, GradientData, TerrainData, TilePos},
+    sprites::TerrainSprite,
+};
+
+/// Tick all placed water mills and update their current production from the flow of the water
+/// beside them.
+///
+/// Flow is the average terrain gradient across the nearby water body. Water in a river all runs
+/// the same way so it flows strongly, whereas the gradients in a still lake cancel each other out.
+pub fn tick_water_mills(
+    water_mills: Query<
+        (
+            &TilePos,
+            &mut PowerProduction,
+            &FlowPower,
+            &MachineSpeed,
+            &mut MachineState,
+            &mut Wear,
+        ),
+        With<WaterMill>,
+    >,
+    chunks: Chunks<(&TerrainData, &GradientData)>,
+    timer: Res<Time>,
+) {
+    // Gradient of a tile if it's water
+    let water_gradient = |tile_pos: &TilePos| {
+        let (chunk_pos, offset) = tile_pos.to_chunk_offset();
+        let (terrain_data, gradient_data) = chunks.get(&chunk_pos)?;
+
+        (terrain_data.0[offset.y as usize][offset.x as usize] == TerrainSprite::Water)
+            .then_some(gradient_data.0[offset.y as usize][offset.x as usize])
+    };
+
+    for (tile_pos, mut current_energy, flow_power, speed, mut state, mut wear) in water_mills {
+        // Flood fill the water body, starting from the water beside the mill
+        let mut stack = tile_pos
+            .adjacent()
+            .filter_map(|pos| water_gradient(&pos).map(|gradient| (pos, gradient)))
+            .collect::<Vec<_>>();
+        let mut visited = stack.iter().map(|(pos, _)| *pos).collect::<HashSet<_>>();
+
+        let mut total_gradient = Vec2::ZERO;
+        while let Some((cur, gradient)) = stack.pop() {
+            total_gradient += gradient;
+
+            for neighbour in cur.adjacent() {
+                if (neighbour.0 - tile_pos.0).abs().max_element() > WATER_MILL_FLOW_RADIUS {
+                    // Too far away to affect this mill
+                    continue;
+                }
+
+                if !visited.contains(&neighbour)
+                    && let Some(gradient) = water_gradient(&neighbour)
+                {
+                    visited.insert(neighbour);
+                    stack.push((neighbour, gradient));
+                }
+            }
+        }
+
+        let flow = if visited.is_empty() {
+            // No water nearby
+            0.
+        } else {
+            (total_gradient / visited.len() as f32).length()
+        };
+
+        // Update energy production rate
+        current_energy.0 = flow * flow_power.0 * wear.efficiency();
+
+        // Update animation, wearing the machine down with each full turn
+        let produced = current_energy.0 * timer.delta_secs();
+        state.0 += produced;
+        if state.0 >= speed.0 {
+            state.0 %= speed.0;
+            wear.add_action();
+        }
+    }
+}
@@ -39,7 +39,9 @@ impl PlacedWindmillBundle {
     pub fn new(tile_pos: TilePos, direction: IVec2) -> Self {
         Self {
             output_direction: Direction(direction),
-            transform: tile_pos.as_transform(Z_RESOURCES),
+            transform: tile_pos
+                .as_transform(Z_RESOURCES)
+                .with_rotation(Quat::from_rotation_z(direction.as_vec2().to_angle())),
             tile_pos,
             placed: Placed,
             targettable: Targettable,
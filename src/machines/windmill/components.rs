@@ -1,5 +1,8 @@
 use bevy::prelude::*;
 
+use super::super::components::Wear;
+
 /// Marker for windmill machines
 #[derive(Component)]
+#[require(Wear)]
 pub struct Windmill;
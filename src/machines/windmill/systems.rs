@@ -12,20 +12,26 @@ pub fn tick_windmills(
             &Direction,
             &mut PowerProduction,
             &MachineSpeed,
+            &MachineTier,
             &mut MachineState,
+            &mut Wear,
         ),
         With<Windmill>,
     >,
 ) {
-    for (direction, mut current_energy, speed, mut state) in windmills {
+    for (direction, mut current_energy, speed, tier, mut state, mut wear) in windmills {
         // Compute alignment in [-1, 1]; only positive alignment produces energy.
         let alignment = direction.0.as_vec2().dot(wind.direction_vec()).max(0.0);
 
         // Update energy production rate
-        current_energy.0 = wind.speed * alignment;
+        current_energy.0 = wind.speed * alignment * tier.speed_multiplier() * wear.efficiency();
 
-        // Update animation
+        // Update animation, wearing the machine down with each full turn
         let produced = current_energy.0 * timer.delta_secs();
-        state.0 = (state.0 + produced) % speed.0;
+        state.0 += produced;
+        if state.0 >= speed.0 {
+            state.0 %= speed.0;
+            wear.add_action();
+        }
     }
 }
@@ -30,10 +30,11 @@ impl TilePos {
     }
 
     /// Get adjacent tile positions
-    pub fn adjacent(&self) -> impl Iterator<Item = Self> {
+    pub fn adjacent(&self) -> impl Iterator<Item = Self> + use<> {
+        let pos = *self;
         [IVec2::X, IVec2::Y, -IVec2::X, -IVec2::Y]
             .into_iter()
-            .map(move |offset| self + offset)
+            .map(move |offset| pos + offset)
     }
 }
 
@@ -11,6 +11,10 @@ impl Plugin for NotificationPlugin {
         app.add_systems(Startup, init_notification_system)
             .add_systems(Update, update_notifications)
             .add_observer(failed_craft)
+            .add_observer(failed_upgrade)
+            .add_observer(failed_repair)
+            .add_observer(machine_broke_down)
+            .add_observer(tool_broke)
             .add_observer(unlock_notification);
     }
 }
@@ -3,7 +3,12 @@ use std::time::Duration;
 use bevy::prelude::*;
 
 use super::components::*;
-use crate::{crafting::FailedCraft, knowledge::UnlockEvent};
+use crate::{
+    crafting::FailedCraft,
+    knowledge::UnlockEvent,
+    machines::{FailedRepair, FailedUpgrade, MachineBrokeDown},
+    tools::ToolBroke,
+};
 
 /// Spawn the notification box
 pub fn init_notification_system(mut commands: Commands) {
@@ -71,3 +76,73 @@ pub fn failed_craft(
         },
     ));
 }
+
+/// Spawns a notification when a machine upgrade is failed
+pub fn failed_upgrade(
+    event: On<FailedUpgrade>,
+    display_box: Single<Entity, With<NotificationBox>>,
+    mut commands: Commands,
+) {
+    commands.entity(*display_box).with_child((
+        Text(format!(
+            "Couldn't upgrade {} {:?}",
+            event.machine, event.reason
+        )),
+        DisplayDuration(Duration::from_secs(5)),
+        Node {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+    ));
+}
+
+/// Spawns a notification when a machine repair is failed
+pub fn failed_repair(
+    event: On<FailedRepair>,
+    display_box: Single<Entity, With<NotificationBox>>,
+    mut commands: Commands,
+) {
+    commands.entity(*display_box).with_child((
+        Text(format!(
+            "Couldn't repair {} {:?}",
+            event.machine, event.reason
+        )),
+        DisplayDuration(Duration::from_secs(5)),
+        Node {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+    ));
+}
+
+/// Spawns a notification when a machine wears out
+pub fn machine_broke_down(
+    event: On<MachineBrokeDown>,
+    display_box: Single<Entity, With<NotificationBox>>,
+    mut commands: Commands,
+) {
+    commands.entity(*display_box).with_child((
+        Text(format!("{} broke down", event.machine)),
+        DisplayDuration(Duration::from_secs(5)),
+        Node {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+    ));
+}
+
+/// Spawns a notification when a tool is used up
+pub fn tool_broke(
+    event: On<ToolBroke>,
+    display_box: Single<Entity, With<NotificationBox>>,
+    mut commands: Commands,
+) {
+    commands.entity(*display_box).with_child((
+        Text(format!("{:?} broke", event.tool)),
+        DisplayDuration(Duration::from_secs(5)),
+        Node {
+            position_type: PositionType::Relative,
+            ..Default::default()
+        },
+    ));
+}
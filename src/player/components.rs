@@ -1,11 +1,31 @@
 use bevy::prelude::*;
 
-use crate::{consts::Z_HELD_ITEM, resources::ResourceNodeType};
+use crate::{
+    consts::Z_HELD_ITEM,
+    map::{TilePos, WorldPos},
+    resources::ResourceNodeType,
+};
 
 /// Marker for player
 #[derive(Component)]
 pub struct Player;
 
+/// World-space position of the mouse cursor, if it's over the window
+#[derive(Resource, Default)]
+pub struct CursorPos(pub Option<WorldPos>);
+impl CursorPos {
+    /// Whether the cursor is over the tile centred on this position
+    pub fn is_over(&self, pos: &WorldPos) -> bool {
+        self.0
+            .is_some_and(|cursor| (cursor.0 - pos.0).abs().max_element() <= 0.5)
+    }
+
+    /// Tile that the cursor is over
+    pub fn tile(&self) -> Option<TilePos> {
+        self.0.map(|cursor| (cursor + Vec2::splat(0.5)).tile())
+    }
+}
+
 /// Marker for things that can be targetted / interacted with by the player
 #[derive(Component)]
 pub struct Targettable;
@@ -35,6 +55,8 @@ pub struct Holding(Vec<Entity>);
 pub struct HarvestEvent {
     pub resource_node: ResourceNodeType,
     pub amount: usize,
+    /// Tool used for the harvest, if any
+    pub tool: Option<Entity>,
     // TODO: Node type / position?
 }
 
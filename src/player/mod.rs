@@ -5,18 +5,20 @@ use bevy::prelude::*;
 pub use components::*;
 use systems::*;
 
-use crate::utils::run_if::{empty_hands, key_just_pressed};
+use crate::utils::run_if::key_just_pressed;
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_player)
+        app.init_resource::<CursorPos>()
+            .add_systems(Startup, setup_player)
             .add_systems(
                 Update,
                 (
                     move_player,
-                    target_thing,
-                    harvest_resource.run_if(key_just_pressed(KeyCode::Space).and(empty_hands)),
+                    (update_cursor_pos, target_thing).chain(),
+                    // Harvested items go into the inventory
+                    harvest_resource.run_if(key_just_pressed(KeyCode::Space)),
                     check_near_water,
                     show_water_icon,
                     harvest_water.run_if(
@@ -2,6 +2,7 @@ use bevy::{
     self,
     ecs::{bundle::InsertMode, system::entity_command},
     prelude::*,
+    window::PrimaryWindow,
 };
 
 use super::components::*;
@@ -10,11 +11,15 @@ use crate::{
         CAMERA_ZOOM, HIGHLIGHT_SCALE, PLAYER_REACH, PLAYER_SPEED, RESOURCE_PICKUP_AMOUNT,
         Z_HELD_ITEM, Z_PLAYER,
     },
-    container::{ContainableItems, ContainedBundle},
-    items::ItemType,
+    container::{ContainableItems, ContainedBundle, ContainerCapacity, Contains},
+    ground_items::GroundItemIndex,
+    inventory::{Inventory, InventorySlot, Stowing},
+    items::{Freshness, ItemType, Quantity},
+    machines::MachineLUT,
     map::{Chunks, TerrainData, TilePos, WorldPos},
-    resources::{ResourceAmount, ResourceMarker, ResourceNodeType},
+    resources::{ResourceAmount, ResourceMarker, ResourceNodeLUT, ResourceNodeType},
     sprites::{EntitySprite, GetSprite, ItemSprite, SpriteSheets, TerrainSprite},
+    tools::Tool,
 };
 
 pub fn setup_player(mut commands: Commands, sprite_sheets: Res<SpriteSheets>) {
@@ -28,6 +33,7 @@ pub fn setup_player(mut commands: Commands, sprite_sheets: Res<SpriteSheets>) {
     let player = commands
         .spawn((
             Player,
+            Inventory::default(),
             world_pos,
             // Render
             world_pos.as_transform(Z_PLAYER),
@@ -64,32 +70,72 @@ pub fn move_player(
     camera.0 = player.0;
 }
 
-/// Targets the closest thing to the player
+/// Track where the mouse cursor is in the world
+pub fn update_cursor_pos(
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera2d>>,
+    mut cursor: ResMut<CursorPos>,
+) {
+    let (camera, camera_transform) = *camera;
+
+    cursor.0 = window
+        .cursor_position()
+        .and_then(|pos| camera.viewport_to_world_2d(camera_transform, pos).ok())
+        .map(WorldPos);
+}
+
+/// Targets the thing under the mouse cursor if it's within reach, falling back to the closest
+/// thing to the player
 pub fn target_thing(
     mut commands: Commands,
     player: Single<(Entity, &WorldPos), With<Player>>,
-    targettables: Query<(Entity, Option<&TilePos>, Option<&WorldPos>), With<Targettable>>,
+    targettables: Query<(), With<Targettable>>,
+    machines: Res<MachineLUT>,
+    resources: Res<ResourceNodeLUT>,
+    ground_items: Res<GroundItemIndex>,
     targetted: Query<Entity, With<TargettedBy>>,
+    cursor: Res<CursorPos>,
 ) {
     let (player_entity, player_pos) = *player;
 
-    let closest = targettables
-        .iter()
-        .map(|(entity, tile_pos, world_pos)| {
-            // Collapse position types
-            let pos = world_pos.copied().unwrap_or_else(|| {
-                tile_pos
-                    .expect("Entity has neither tile or world position")
-                    .as_world_pos()
-            });
-
-            // Calculate distance
-            let distance2 = player_pos.0.distance_squared(pos.0);
-
-            (entity, pos, distance2)
+    // Machines & resources on the tiles around the player. Bigger machines cover several tiles,
+    // so they can be reached & hovered over through any of them.
+    let min = (player_pos - Vec2::splat(PLAYER_REACH - 0.5)).tile().0;
+    let max = (player_pos + Vec2::splat(PLAYER_REACH + 0.5)).tile().0;
+    let on_tiles = (min.y..=max.y)
+        .flat_map(|y| (min.x..=max.x).map(move |x| TilePos(IVec2::new(x, y))))
+        .flat_map(|tile_pos| {
+            [machines.get(&tile_pos), resources.0.get(&tile_pos)]
+                .into_iter()
+                .flatten()
+                .map(move |entity| (*entity, tile_pos.as_world_pos()))
         })
-        .filter(|(_, _, distance2)| *distance2 <= PLAYER_REACH.powi(2))
-        .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2));
+        .filter(|(entity, _)| targettables.contains(*entity))
+        .map(|(entity, pos)| (entity, pos, player_pos.0.distance_squared(pos.0)))
+        .filter(|(_, _, distance2)| *distance2 <= PLAYER_REACH.powi(2));
+
+    let in_reach = on_tiles
+        .chain(ground_items.in_radius(player_pos, PLAYER_REACH))
+        .collect::<Vec<_>>();
+
+    // Things under the cursor take precedence, picking the one closest to the cursor
+    let hovered = cursor.0.and_then(|cursor_pos| {
+        in_reach
+            .iter()
+            .filter(|(_, pos, _)| cursor.is_over(pos))
+            .min_by(|(_, p1, _), (_, p2, _)| {
+                let d1 = p1.0.distance_squared(cursor_pos.0);
+                let d2 = p2.0.distance_squared(cursor_pos.0);
+                d1.total_cmp(&d2)
+            })
+            .copied()
+    });
+
+    let closest = hovered.or_else(|| {
+        in_reach
+            .into_iter()
+            .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))
+    });
 
     for entity in targetted {
         if let Some((closest_entity, _, _)) = closest
@@ -143,16 +189,22 @@ pub fn make_untargettable(event: On<Remove, Targettable>, mut commands: Commands
     commands.entity(event.entity).try_remove::<TargettedBy>();
 }
 
-/// Pick up a resource and put it in the player's hand
+/// Pick up a resource and put it in the player's inventory, using the held tool if it works on
+/// the node. Some nodes can't be harvested without a tool.
 pub fn harvest_resource(
     mut commands: Commands,
-    player: Single<Entity, With<Player>>,
+    player: Single<(Entity, &Inventory, Option<&Holding>, Option<&Stowing>), With<Player>>,
     mut targetted_resources: Populated<
         (&ResourceNodeType, &ItemType, &mut ResourceAmount),
         (With<ResourceMarker>, With<TargettedBy>, Without<Player>),
     >,
+    tools: Query<&Tool, With<HeldBy>>,
+    stowed_items: Query<&InventorySlot>,
+    mut items: Query<(&ItemType, &mut Quantity, Option<&mut Freshness>), Without<ResourceMarker>>,
     sprite_sheets: Res<SpriteSheets>,
 ) {
+    let (player, inventory, holding, stowing) = *player;
+
     let (node_type, item_type, mut amount) = targetted_resources
         .iter_mut()
         .next()
@@ -163,27 +215,63 @@ pub fn harvest_resource(
         return;
     }
 
+    let tool = holding
+        .and_then(|holding| holding.iter().next())
+        .and_then(|held| tools.get(held).ok().map(|tool| (held, tool)))
+        .filter(|(_, tool)| tool.works_on(node_type));
+    if tool.is_none() && node_type.needs_tool() {
+        info!("Need a tool to harvest {:?}", node_type);
+        return;
+    }
+
     info!("Harvesting resource: {:?}", node_type);
 
-    let pickup_amount = RESOURCE_PICKUP_AMOUNT.min(amount.0);
+    let pickup_amount = tool
+        .map_or(RESOURCE_PICKUP_AMOUNT, |(_, tool)| tool.pickup_amount)
+        .min(amount.0);
 
-    // Add item to player
-    let entity = commands
-        .spawn((
-            HeldItemBundle::new(*player),
-            // Game data
-            *item_type,
-        ))
-        .id();
+    // Top up stacks of the same item in the inventory first
+    let slots = inventory.slots(holding, stowing, &stowed_items);
+    let mut remaining = pickup_amount;
+    for stack in slots.iter().flatten() {
+        let Ok((stack_type, mut quantity, freshness)) = items.get_mut(*stack) else {
+            continue;
+        };
+        let added = remaining.min(item_type.max_stack_size().saturating_sub(quantity.0));
+        if stack_type != item_type || added == 0 {
+            continue;
+        }
 
-    item_type.spawn_sprite(&mut commands, &sprite_sheets, Some(entity));
+        // Freshly picked items perk the stack up a bit
+        if let Some(mut freshness) = freshness {
+            freshness.blend(quantity.0, added, 1.);
+        }
+        quantity.0 += added;
+        remaining -= added;
+    }
+
+    // Then start a new stack with whatever's left
+    if remaining > 0
+        && let Some(slot) = inventory.free_slot(&slots)
+    {
+        let entity = item_type.spawn_stack(&mut commands, &sprite_sheets, remaining);
+        inventory.put_in_slot(&mut commands.entity(entity), player, slot);
+        remaining = 0;
+    }
+
+    let harvested = pickup_amount - remaining;
+    if harvested == 0 {
+        info!("Inventory is full");
+        return;
+    }
 
     // Subtract the pickup amount
-    amount.0 -= pickup_amount;
+    amount.0 -= harvested;
 
     commands.trigger(HarvestEvent {
         resource_node: *node_type,
-        amount: pickup_amount,
+        amount: harvested,
+        tool: tool.map(|(tool, _)| tool),
     });
 }
 
@@ -272,39 +360,82 @@ pub fn show_water_icon(
     }
 }
 
-/// Pick up some water from an infinite source
+/// Pick up some water from an infinite source, into the held container or stack of water
 pub fn harvest_water(
     mut commands: Commands,
     player: Single<(Entity, Option<&Holding>), (With<Player>, With<NearWater>)>,
-    containers: Query<&ContainableItems>,
+    containers: Query<(&ContainableItems, &ContainerCapacity, Option<&Contains>)>,
+    mut items: Query<(&ItemType, &mut Quantity, Option<&mut Freshness>)>,
+    tools: Query<&Tool>,
     sprite_sheets: Res<SpriteSheets>,
 ) {
-    let container = player.1.and_then(|holding| {
-        let item = holding.iter().next().unwrap();
-
-        containers
-            .get(item)
-            .ok()
-            .filter(|containables| containables.0.contains(&ItemType::Water))
-            .map(|_| item)
-    });
-
-    // Spawn water item in the void
-    let item = commands.spawn(ItemType::Water).id();
-    ItemSprite::Water.spawn_sprite(&mut commands, &sprite_sheets, Some(item));
+    let (player, holding) = *player;
+
+    // Tools like buckets scoop up more at once
+    let tool = holding
+        .and_then(|holding| holding.iter().next())
+        .filter(|held| {
+            tools
+                .get(*held)
+                .is_ok_and(|tool| tool.works_on(&ResourceNodeType::Water))
+        });
+    let amount = tool
+        .and_then(|tool| tools.get(tool).ok())
+        .map_or(RESOURCE_PICKUP_AMOUNT, |tool| tool.pickup_amount);
+    let has_room = |item_type: &ItemType, quantity: &Quantity| {
+        *item_type == ItemType::Water && quantity.0 + amount <= item_type.max_stack_size()
+    };
 
-    if let Some(container) = container {
-        // Put the item in the container
-        commands
-            .entity(item)
-            .insert(ContainedBundle::new(container));
+    if let Some(held) = holding.and_then(|holding| holding.iter().next()) {
+        if let Ok((containables, capacity, contains)) = containers.get(held)
+            && containables.0.contains(&ItemType::Water)
+        {
+            // Top up a stack of water that's already in the container if there's room
+            let stack = contains
+                .into_iter()
+                .flat_map(|contains| contains.iter())
+                .find(|item| {
+                    items
+                        .get(*item)
+                        .is_ok_and(|(item_type, quantity, _)| has_room(item_type, quantity))
+                });
+
+            if let Some(stack) = stack {
+                let (_, mut quantity, freshness) =
+                    items.get_mut(stack).expect("Stack was just found");
+                if let Some(mut freshness) = freshness {
+                    freshness.blend(quantity.0, amount, 1.);
+                }
+                quantity.0 += amount;
+            } else if capacity.has_room(contains) {
+                // Put a new stack in the container
+                let item = ItemType::Water.spawn_stack(&mut commands, &sprite_sheets, amount);
+                commands.entity(item).insert(ContainedBundle::new(held));
+            } else {
+                // Container is full
+                return;
+            }
+        } else if let Ok((item_type, mut quantity, freshness)) = items.get_mut(held)
+            && has_room(item_type, &quantity)
+        {
+            // Top up the held stack of water
+            if let Some(mut freshness) = freshness {
+                freshness.blend(quantity.0, amount, 1.);
+            }
+            quantity.0 += amount;
+        } else {
+            // Hands are full
+            return;
+        }
     } else {
         // Give item to player directly
-        commands.entity(item).insert(HeldItemBundle::new(player.0));
+        let item = ItemType::Water.spawn_stack(&mut commands, &sprite_sheets, amount);
+        commands.entity(item).insert(HeldItemBundle::new(player));
     }
 
     commands.trigger(HarvestEvent {
         resource_node: ResourceNodeType::Water,
-        amount: RESOURCE_PICKUP_AMOUNT,
+        amount,
+        tool,
     });
 }
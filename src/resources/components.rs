@@ -33,6 +33,15 @@ impl ResourceNodeType {
             ResourceNodeType::Water => unreachable!("Water node should never be rendered"),
         }
     }
+
+    /// Whether a tool is needed to harvest this node by hand
+    pub fn needs_tool(&self) -> bool {
+        match self {
+            ResourceNodeType::Tree => true,
+            ResourceNodeType::Bush => false,
+            ResourceNodeType::Water => false,
+        }
+    }
 }
 
 impl GetSprite for ResourceNodeType {
@@ -54,6 +54,17 @@ pub enum EntitySprite {
     WaterWheel2,
     Windmill1,
     Windmill2,
+    Battery,
+    PowerPole,
+    WaterMill1,
+    WaterMill2,
+    Burner1,
+    Burner2,
+    UndergroundEntrance,
+    UndergroundExit,
+    Feller1,
+    Feller2,
+    Collector,
 }
 
 /// Indexes into item_sheet.png
@@ -70,6 +81,20 @@ pub enum ItemSprite {
     TripAxe,
     WaterWheel,
     Windmill,
+    Battery,
+    PowerPole,
+    WaterMill,
+    Burner,
+    UndergroundEntrance,
+    UndergroundExit,
+    Feller,
+    Collector,
+    Bucket,
+    Basket,
+    Crate,
+    Rotten,
+    Axe,
+    Sickle,
 }
 
 /// Holds a spritesheet image & layout info
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+use crate::{items::ItemType, resources::ResourceNodeType};
+
+/// A hand tool which helps the player harvest certain kinds of resource node
+#[derive(Component, Clone, Debug)]
+pub struct Tool {
+    /// Nodes the tool works on
+    pub node_types: Vec<ResourceNodeType>,
+    /// How much is harvested each time the tool is used
+    pub pickup_amount: usize,
+}
+impl Tool {
+    /// Whether this tool can be used on the given type of node
+    pub fn works_on(&self, node_type: &ResourceNodeType) -> bool {
+        self.node_types.contains(node_type)
+    }
+}
+
+/// Uses left before a tool breaks
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Durability {
+    pub remaining: usize,
+    pub max: usize,
+}
+impl Durability {
+    pub fn new(max: usize) -> Self {
+        Self {
+            remaining: max,
+            max,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct ToolBundle {
+    tool: Tool,
+    durability: Durability,
+}
+impl ToolBundle {
+    pub fn new(
+        node_types: impl IntoIterator<Item = ResourceNodeType>,
+        pickup_amount: usize,
+        durability: usize,
+    ) -> Self {
+        Self {
+            tool: Tool {
+                node_types: node_types.into_iter().collect(),
+                pickup_amount,
+            },
+            durability: Durability::new(durability),
+        }
+    }
+}
+
+/// Event thrown when a tool is used up
+#[derive(Event, Debug)]
+pub struct ToolBroke {
+    pub tool: ItemType,
+}
@@ -0,0 +1,13 @@
+mod components;
+mod systems;
+
+use bevy::prelude::*;
+pub use components::*;
+use systems::*;
+
+pub struct ToolPlugin;
+impl Plugin for ToolPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(wear_tools);
+    }
+}
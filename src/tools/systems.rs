@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+use super::components::*;
+use crate::{
+    container::{ContainedBundle, Contains},
+    ground_items::GroundItemBundle,
+    items::ItemType,
+    map::WorldPos,
+    player::{HarvestEvent, Player},
+};
+
+/// Wear down the tool used for a harvest, breaking it once it's used up. Anything in a broken
+/// container tool is spilled on the ground.
+pub fn wear_tools(
+    event: On<HarvestEvent>,
+    mut tools: Query<(&ItemType, &mut Durability, Option<&Contains>)>,
+    player_pos: Single<&WorldPos, With<Player>>,
+    mut commands: Commands,
+) {
+    let Some(tool) = event.tool else {
+        // Harvested by hand
+        return;
+    };
+    let Ok((item_type, mut durability, contains)) = tools.get_mut(tool) else {
+        return;
+    };
+
+    durability.remaining = durability.remaining.saturating_sub(1);
+    if durability.remaining == 0 {
+        info!("Tool broke: {:?}", item_type);
+
+        // Contained items are children of the container, so get them out before it goes
+        for item in contains.into_iter().flat_map(|contains| contains.iter()) {
+            commands
+                .entity(item)
+                .remove::<ContainedBundle>()
+                .insert(GroundItemBundle::new(*player_pos));
+        }
+
+        commands.entity(tool).despawn();
+        commands.trigger(ToolBroke { tool: *item_type });
+    }
+}
@@ -1,11 +1,47 @@
 use bevy::prelude::*;
 
-use crate::player::{Holding, Player};
+use crate::{
+    map::{TilePos, WorldPos},
+    player::{CursorPos, Holding, Player, TargettedBy},
+};
 
 pub fn key_just_pressed(key: KeyCode) -> impl FnMut(Res<ButtonInput<KeyCode>>) -> bool {
     move |inputs| inputs.just_pressed(key)
 }
 
+/// Mouse clicks on UI elements aren't passed through to the world
+pub fn mouse_just_pressed(
+    button: MouseButton,
+) -> impl FnMut(Res<ButtonInput<MouseButton>>, Query<&Interaction>) -> bool {
+    move |inputs, interactions| {
+        inputs.just_pressed(button)
+            && interactions
+                .iter()
+                .all(|interaction| *interaction == Interaction::None)
+    }
+}
+
+pub fn mouse_just_released(
+    button: MouseButton,
+) -> impl FnMut(Res<ButtonInput<MouseButton>>) -> bool {
+    move |inputs| inputs.just_released(button)
+}
+
+/// Whether the mouse cursor is over the player's current target
+pub fn cursor_over_target(
+    targets: Query<(Option<&TilePos>, Option<&WorldPos>), With<TargettedBy>>,
+    cursor: Res<CursorPos>,
+) -> bool {
+    targets.iter().any(|(tile_pos, world_pos)| {
+        let pos = world_pos
+            .copied()
+            .or_else(|| tile_pos.map(TilePos::as_world_pos));
+
+        pos.is_some_and(|pos| cursor.is_over(&pos))
+    })
+}
+
+/// Whether the player's selected inventory slot is empty
 pub fn empty_hands(held_item: Single<Has<Holding>, With<Player>>) -> bool {
     !(*held_item)
 }
@@ -5,7 +5,7 @@ use crate::{
     consts::Z_RESOURCES,
     container::{ContainedBundle, ContainedBy, Container, Contains},
     items::ItemType,
-    machines::{AcceptsItems, Machine, MachineLUT, Placed, TransferItem},
+    machines::{AcceptsItems, Machine, MachineLUT, MachinePlacement, Placed, TransferItem},
     map::TilePos,
     player::{HeldBy, HeldItemBundle, Targettable, TargettedBy},
     resources::ResourceType,
@@ -93,6 +93,7 @@ pub fn spawn_village_centre(
     mut commands: Commands,
     sprite_sheets: Res<SpriteSheets>,
     mut machine_lut: ResMut<MachineLUT>,
+    mut placements: MessageWriter<MachinePlacement>,
 ) {
     let pos = TilePos(IVec2::ZERO);
     let village = commands
@@ -110,6 +111,7 @@ pub fn spawn_village_centre(
     ResourceSprite::House.spawn_sprite(&mut commands, &sprite_sheets, Some(village));
 
     machine_lut.0.insert(pos, village);
+    placements.write(MachinePlacement::Placed(pos));
 }
 
 /// Deposit a held item into the village